@@ -0,0 +1,787 @@
+//! BTCUSDT viewer backed by Binance.
+//!
+//! Run with `cargo run --example binance_viewer`, or add `-- --fixture` to use
+//! generated data offline. `-- --trades <file>` also runs offline, with BTCUSDT
+//! range and volume bars built from trades recorded as `timestamp_ms,price,quantity`.
+
+use iced_candlestick::{
+    axis, trades, BarKind, BarUpdate, BinanceSource, BoxSize, Candle, CandlestickChart, ChartMessage, ChartType, DataSource,
+    DisplayTimezone, FixtureSource, Interval, Overlay, PaneKind, ScaleMode, Session, SymbolInfo, Trade, TradeAggregator, VolumeUnit,
+};
+use std::fmt;
+use iced::widget::{button, checkbox, column, container, pick_list, row, text};
+use iced::time::Instant;
+use iced::{window, Color, Element, Subscription, Task};
+
+fn main() -> iced::Result {
+    let args: Vec<String> = std::env::args().collect();
+    let trades_file = args.iter().position(|arg| arg == "--trades").and_then(|i| args.get(i + 1));
+
+    // `--fixture` runs offline on generated data
+    let source: Box<dyn DataSource> = if args.iter().any(|arg| arg == "--fixture") || trades_file.is_some() {
+        let mut fixture = FixtureSource::random_walk(
+            &[("BTCUSDT", 60000.0), ("ETHUSDT", 3000.0), ("SHIBUSDT", 0.00002)],
+            2000,
+        );
+        if let Some(path) = trades_file {
+            match trades::read_trades(path) {
+                Ok(recorded) => fixture = fixture.with_trades("BTCUSDT", recorded),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Box::new(fixture)
+    } else {
+        let mut binance = BinanceSource::new();
+        // Override to point the live stream at a local stand-in
+        if let Ok(endpoint) = std::env::var("BINANCE_STREAM_ENDPOINT") {
+            binance = binance.with_stream_endpoint(endpoint);
+        }
+        Box::new(binance)
+    };
+
+    iced::application(App::title, App::update, App::view)
+        .subscription(App::subscription)
+        .run_with(move || App::new(source))
+}
+
+/// What each bar of the chart stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+enum BarMode {
+    /// Candles of the selected interval
+    #[default]
+    Time,
+    /// Bars of a fixed price range, built from trades
+    Range,
+    /// Bars of a fixed traded volume, built from trades
+    Volume,
+}
+
+impl BarMode {
+    const ALL: [BarMode; 3] = [BarMode::Time, BarMode::Range, BarMode::Volume];
+
+    /// Bar size for `trades`, a small fraction of the price range or enough
+    /// volume for about a hundred bars
+    fn kind(self, trades: &[Trade]) -> Option<BarKind> {
+        match self {
+            BarMode::Time => None,
+            BarMode::Range => {
+                let price = trades.last()?.price;
+                Some(BarKind::Range(axis::nice_step(price * 0.0002)))
+            }
+            BarMode::Volume => {
+                let total: f64 = trades.iter().map(|t| t.quantity).sum();
+                Some(BarKind::Volume(axis::nice_step(total / 100.0)))
+            }
+        }
+    }
+}
+
+/// The series a fetch or stream was for, so results arriving after a switch are dropped
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Series {
+    symbol: String,
+    interval: Interval,
+    bar_mode: BarMode,
+}
+
+impl fmt::Display for BarMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BarMode::Time => "Time Bars",
+            BarMode::Range => "Range Bars",
+            BarMode::Volume => "Volume Bars",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Indicators offered over the prices, with their colors
+const OVERLAYS: [(Overlay, Color); 7] = [
+    (Overlay::Sma(20), Color::from_rgb(1.0, 0.8, 0.2)),
+    (Overlay::Ema(50), Color::from_rgb(0.9, 0.4, 0.9)),
+    (Overlay::Wma(100), Color::from_rgb(0.3, 0.9, 0.9)),
+    (Overlay::Bollinger { period: 20, width: 2.0 }, Color::from_rgb(0.5, 0.6, 1.0)),
+    (Overlay::Vwap { session: Session::Day, bands: None }, Color::from_rgb(1.0, 0.5, 0.3)),
+    (Overlay::Vwap { session: Session::Week, bands: Some(1.0) }, Color::from_rgb(0.6, 0.9, 0.4)),
+    (Overlay::Ichimoku { tenkan: 9, kijun: 26, senkou: 52 }, Color::from_rgb(0.9, 0.9, 0.9)),
+];
+
+/// Color of the VWAP anchored at a clicked candle
+const ANCHORED_VWAP_COLOR: Color = Color::from_rgb(1.0, 0.4, 0.7);
+
+/// VWAP anchored at a clicked candle, with one standard deviation bands
+fn anchored_vwap(anchor: i64) -> Overlay {
+    Overlay::AnchoredVwap { anchor, bands: Some(1.0) }
+}
+
+/// Panes offered below the prices
+const PANES: [PaneKind; 5] = [
+    PaneKind::Volume,
+    PaneKind::Rsi(14),
+    PaneKind::Macd { fast: 12, slow: 26, signal: 9 },
+    PaneKind::Stochastic { period: 14, smooth: 3, signal: 3 },
+    PaneKind::Atr(14),
+];
+
+struct App {
+    source: Box<dyn DataSource>,
+    symbol: SymbolInfo,
+    symbols: Vec<SymbolInfo>,
+    chart: Option<CandlestickChart>,
+    shown: Option<Series>, // Series in the chart, behind a switch until its data arrives
+    selected_interval: Interval,
+    bar_mode: BarMode,
+    aggregator: Option<TradeAggregator>, // Builds range and volume bars from live trades
+    last_trade_id: u64,                  // Id of the last trade put into bars
+    chart_type: ChartType,
+    box_size: BoxSize,
+    reversal: usize,
+    scale_mode: ScaleMode,
+    panes: Vec<PaneKind>, // Below the prices, top to bottom
+    volume_unit: VolumeUnit,
+    overlays: Vec<Overlay>, // Shown, out of `OVERLAYS`
+    anchor: Option<i64>,    // Start of the anchored VWAP
+    anchoring: bool,        // Next candle clicked starts the anchored VWAP
+    timezone: DisplayTimezone,
+    loading: bool,
+    error: Option<String>,
+    stream_error: Option<String>,
+    loading_history: bool,
+    history_exhausted: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    SymbolsFetched(Result<Vec<SymbolInfo>, String>),
+    SymbolSelected(SymbolInfo),
+    IntervalSelected(Interval),
+    BarModeSelected(BarMode),
+    ChartTypeSelected(ChartType),
+    BoxSizeSelected(BoxSize),
+    ReversalSelected(usize),
+    ScaleModeSelected(ScaleMode),
+    PaneToggled(PaneKind, bool),
+    VolumeUnitSelected(VolumeUnit),
+    OverlayToggled(Overlay, bool),
+    AnchorPressed,
+    TimezoneSelected(DisplayTimezone),
+    DataFetched(Series, Result<Vec<Candle>, String>),
+    HistoryFetched(Series, Result<Vec<Candle>, String>),
+    RefreshData,
+    KlineReceived(Series, Result<Candle, String>),
+    TradesFetched(Series, Result<Vec<Trade>, String>),
+    TradeReceived(Series, Result<Trade, String>),
+    ChartEvent(ChartMessage),
+    Frame(Instant),
+}
+
+impl App {
+    fn new(source: Box<dyn DataSource>) -> (Self, Task<Message>) {
+        let app = Self {
+            source,
+            symbol: SymbolInfo::new("BTCUSDT", 2),
+            symbols: Vec::new(),
+            chart: None,
+            shown: None,
+            selected_interval: Interval::default(),
+            bar_mode: BarMode::default(),
+            aggregator: None,
+            last_trade_id: 0,
+            chart_type: ChartType::default(),
+            box_size: BoxSize::default(),
+            reversal: ChartType::default().default_reversal(),
+            scale_mode: ScaleMode::default(),
+            panes: vec![PaneKind::Volume],
+            volume_unit: VolumeUnit::default(),
+            overlays: Vec::new(),
+            anchor: None,
+            anchoring: false,
+            timezone: DisplayTimezone::default(),
+            loading: false,
+            error: None,
+            stream_error: None,
+            loading_history: false,
+            history_exhausted: false,
+        };
+
+        // Fetch initial data
+        let task = Task::batch([
+            Task::perform(app.source.symbols(), Message::SymbolsFetched),
+            app.fetch_data(),
+        ]);
+
+        (app, task)
+    }
+
+    fn title(&self) -> String {
+        format!("{} - {}", self.symbol.name, self.source.name())
+    }
+
+    /// The series currently shown
+    fn series(&self) -> Series {
+        Series {
+            symbol: self.symbol.name.clone(),
+            interval: self.selected_interval,
+            bar_mode: self.bar_mode,
+        }
+    }
+
+    fn fetch_klines(&self) -> Task<Message> {
+        let series = self.series();
+        Task::perform(
+            self.source.klines(&self.symbol.name, self.selected_interval, 500),
+            move |result| Message::DataFetched(series.clone(), result),
+        )
+    }
+
+    /// Fetch candles, or the trades to build bars from
+    fn fetch_data(&self) -> Task<Message> {
+        let series = self.series();
+        match self.bar_mode {
+            BarMode::Time => self.fetch_klines(),
+            BarMode::Range | BarMode::Volume => Task::perform(
+                self.source.trades(&self.symbol.name, 1000),
+                move |result| Message::TradesFetched(series.clone(), result),
+            ),
+        }
+    }
+
+    /// Chart of `candles` with the current settings
+    fn new_chart(&self, candles: Vec<Candle>, interval_minutes: i64) -> CandlestickChart {
+        let mut chart = CandlestickChart::new(candles, interval_minutes)
+            .chart_type(self.chart_type)
+            .box_size(self.box_size)
+            .reversal(self.reversal)
+            .price_precision(self.symbol.price_precision)
+            .scale_mode(self.scale_mode)
+            .volume_unit(self.volume_unit)
+            .timezone(self.timezone);
+        chart.set_future_margin(chart.bar_duration() * self.future_bars());
+
+        for (overlay, color) in OVERLAYS {
+            if self.overlays.contains(&overlay) {
+                chart.add_overlay(overlay, color);
+            }
+        }
+        if let Some(anchor) = self.anchor {
+            chart.add_overlay(anchored_vwap(anchor), ANCHORED_VWAP_COLOR);
+        }
+        for kind in &self.panes {
+            chart.add_pane(*kind);
+        }
+        chart
+    }
+
+    /// Bars to leave empty right of the newest candle, enough for a projected
+    /// Ichimoku cloud
+    fn future_bars(&self) -> u32 {
+        self.overlays
+            .iter()
+            .map(|overlay| match overlay {
+                Overlay::Ichimoku { kijun, .. } => *kijun as u32,
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Fetch the page of candles preceding the oldest loaded one
+    fn fetch_history(&mut self) -> Task<Message> {
+        let Some(chart) = self.chart.as_mut() else {
+            return Task::none();
+        };
+        let Some(first) = chart.candles().first().map(|c| c.timestamp) else {
+            return Task::none();
+        };
+
+        if self.loading_history || self.history_exhausted {
+            return Task::none();
+        }
+
+        self.loading_history = true;
+        chart.set_loading_history(true);
+
+        let series = self.series();
+        Task::perform(
+            self.source.klines_before(&series.symbol, series.interval, first, 500),
+            move |result| Message::HistoryFetched(series.clone(), result),
+        )
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SymbolsFetched(result) => {
+                // The symbol picker is optional, keep going without it
+                if let Ok(symbols) = result {
+                    // Pick up the real precision of the symbol shown at startup
+                    if let Some(info) = symbols.iter().find(|s| s.name == self.symbol.name) {
+                        self.symbol = info.clone();
+                        if let Some(chart) = self.chart.as_mut() {
+                            chart.set_price_precision(info.price_precision);
+                        }
+                    }
+                    self.symbols = symbols;
+                }
+
+                Task::none()
+            }
+            Message::SymbolSelected(symbol) => {
+                self.symbol = symbol;
+                self.loading = true;
+                self.error = None;
+
+                self.fetch_data()
+            }
+            Message::IntervalSelected(interval) => {
+                self.selected_interval = interval;
+                self.loading = true;
+                self.error = None;
+
+                self.fetch_data()
+            }
+            Message::BarModeSelected(bar_mode) => {
+                self.bar_mode = bar_mode;
+                self.loading = true;
+                self.error = None;
+
+                self.fetch_data()
+            }
+            Message::ChartTypeSelected(chart_type) => {
+                self.chart_type = chart_type;
+                self.reversal = chart_type.default_reversal();
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_reversal(self.reversal);
+                    chart.set_chart_type(chart_type);
+                }
+
+                Task::none()
+            }
+            Message::BoxSizeSelected(box_size) => {
+                self.box_size = box_size;
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_box_size(box_size);
+                }
+
+                Task::none()
+            }
+            Message::ReversalSelected(reversal) => {
+                self.reversal = reversal;
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_reversal(reversal);
+                }
+
+                Task::none()
+            }
+            Message::ScaleModeSelected(mode) => {
+                self.scale_mode = mode;
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_scale_mode(mode);
+                }
+
+                Task::none()
+            }
+            Message::PaneToggled(kind, shown) => {
+                if let Some(chart) = self.chart.as_mut() {
+                    let existing = chart.panes().find(|(_, k)| *k == kind).map(|(id, _)| id);
+                    match existing {
+                        Some(id) if !shown => chart.remove_pane(id),
+                        None if shown => {
+                            chart.add_pane(kind);
+                        }
+                        _ => {}
+                    }
+                    self.panes = chart.panes().map(|(_, kind)| kind).collect();
+                } else {
+                    self.panes.retain(|k| *k != kind);
+                    if shown {
+                        self.panes.push(kind);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::VolumeUnitSelected(unit) => {
+                self.volume_unit = unit;
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_volume_unit(unit);
+                }
+
+                Task::none()
+            }
+            Message::OverlayToggled(overlay, shown) => {
+                self.overlays.retain(|o| *o != overlay);
+                if shown {
+                    self.overlays.push(overlay);
+                }
+                let future_bars = self.future_bars();
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_future_margin(chart.bar_duration() * future_bars);
+                    match OVERLAYS.iter().find(|(o, _)| *o == overlay) {
+                        Some((_, color)) if shown => chart.add_overlay(overlay, *color),
+                        _ => chart.remove_overlay(overlay),
+                    }
+                }
+
+                Task::none()
+            }
+            Message::AnchorPressed => {
+                // Clears the anchored VWAP, or waits for a candle to start it at
+                match self.anchor.take() {
+                    Some(anchor) => {
+                        if let Some(chart) = self.chart.as_mut() {
+                            chart.remove_overlay(anchored_vwap(anchor));
+                        }
+                    }
+                    None => self.anchoring = !self.anchoring,
+                }
+
+                Task::none()
+            }
+            Message::TimezoneSelected(timezone) => {
+                self.timezone = timezone;
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.set_timezone(timezone);
+                }
+
+                Task::none()
+            }
+            Message::DataFetched(series, result) => {
+                // Drop candles for a series that is no longer shown
+                if series != self.series() {
+                    return Task::none();
+                }
+                self.loading = false;
+
+                match result {
+                    Ok(candles) => {
+                        self.loading_history = false;
+                        self.history_exhausted = false;
+                        self.aggregator = None;
+                        self.chart = Some(self.new_chart(candles, self.selected_interval.to_minutes()));
+                        self.shown = Some(series);
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::HistoryFetched(series, result) => {
+                // Drop pages for a series that is no longer shown
+                if series != self.series() {
+                    return Task::none();
+                }
+
+                self.loading_history = false;
+                let Some(chart) = self.chart.as_mut() else {
+                    return Task::none();
+                };
+                chart.set_loading_history(false);
+
+                match result {
+                    Ok(older) => {
+                        if chart.prepend_history(older) == 0 {
+                            self.history_exhausted = true;
+                        }
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        self.error = Some(e);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::RefreshData => {
+                self.loading = true;
+                self.error = None;
+
+                self.fetch_data()
+            }
+            Message::KlineReceived(series, result) => {
+                // The stream of a new series starts before its candles arrive
+                if self.shown.as_ref() != Some(&series) {
+                    return Task::none();
+                }
+
+                match result {
+                    Ok(candle) => {
+                        self.stream_error = None;
+                        if let Some(chart) = self.chart.as_mut() {
+                            chart.push_candle(candle);
+                        }
+                    }
+                    Err(e) => {
+                        self.stream_error = Some(e);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::TradesFetched(series, result) => {
+                // Drop trades for a series that is no longer shown
+                if series != self.series() {
+                    return Task::none();
+                }
+                self.loading = false;
+
+                match result {
+                    Ok(trades) => match self.bar_mode.kind(&trades) {
+                        Some(kind) => {
+                            let mut aggregator = TradeAggregator::new(kind);
+                            let bars = aggregator.aggregate(&trades);
+                            self.last_trade_id = trades.last().map_or(0, |t| t.id);
+                            self.aggregator = Some(aggregator);
+
+                            // Only the latest trades are available, there is no paging back
+                            self.loading_history = false;
+                            self.history_exhausted = true;
+                            self.chart = Some(self.new_chart(bars, 0));
+                            self.shown = Some(series);
+                            self.error = None;
+                        }
+                        None => self.error = Some("No trades to build bars from".to_string()),
+                    },
+                    Err(e) => {
+                        self.error = Some(e);
+                    }
+                }
+
+                Task::none()
+            }
+            Message::TradeReceived(series, result) => {
+                if self.shown.as_ref() != Some(&series) {
+                    return Task::none();
+                }
+                let trade = match result {
+                    Ok(trade) => trade,
+                    Err(e) => {
+                        self.stream_error = Some(e);
+                        return Task::none();
+                    }
+                };
+                self.stream_error = None;
+
+                // Skip trades the fetched ones already covered, which may
+                // share their last millisecond with new ones
+                if trade.id <= self.last_trade_id {
+                    return Task::none();
+                }
+                self.last_trade_id = trade.id;
+
+                if let (Some(aggregator), Some(chart)) = (self.aggregator.as_mut(), self.chart.as_mut()) {
+                    match aggregator.push(&trade) {
+                        BarUpdate::Opened(bar) => chart.append_candle(bar),
+                        BarUpdate::Updated(bar) => chart.replace_last_candle(bar),
+                    }
+                }
+
+                Task::none()
+            }
+            Message::ChartEvent(chart_msg) => self.chart_event(chart_msg),
+            Message::Frame(now) => {
+                match self.chart.as_ref().and_then(|chart| chart.tick(now)) {
+                    Some(chart_msg) => self.chart_event(chart_msg),
+                    None => Task::none(),
+                }
+            }
+        }
+    }
+
+    fn chart_event(&mut self, message: ChartMessage) -> Task<Message> {
+        match message {
+            // The chart redraws itself, nothing to keep in sync here
+            ChartMessage::ViewportChanged { .. } => Task::none(),
+            ChartMessage::CandleClicked(candle) => {
+                if self.anchoring
+                    && let Some(chart) = self.chart.as_mut()
+                {
+                    self.anchoring = false;
+                    self.anchor = Some(candle.timestamp);
+                    chart.add_overlay(anchored_vwap(candle.timestamp), ANCHORED_VWAP_COLOR);
+                }
+                Task::none()
+            }
+            ChartMessage::NeedMoreHistory => self.fetch_history(),
+            ChartMessage::PaneCloseRequested(pane) => {
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.remove_pane(pane);
+                    self.panes = chart.panes().map(|(_, kind)| kind).collect();
+                }
+                Task::none()
+            }
+            ChartMessage::PaneMoveRequested { pane, up } => {
+                // Keep the order for charts built after a symbol or interval change
+                if let Some(chart) = self.chart.as_mut() {
+                    chart.move_pane(pane, up);
+                    self.panes = chart.panes().map(|(_, kind)| kind).collect();
+                }
+                Task::none()
+            }
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // Tagged with their series, as the chart holds the previous one for a while
+        let series = self.series();
+        let live = match self.bar_mode {
+            BarMode::Time => self
+                .source
+                .live(&self.symbol.name, self.selected_interval)
+                .map(|live| live.with(series).map(|(series, result)| Message::KlineReceived(series, result))),
+            BarMode::Range | BarMode::Volume => self
+                .source
+                .live_trades(&self.symbol.name)
+                .map(|live| live.with(series).map(|(series, result)| Message::TradeReceived(series, result))),
+        }
+        .unwrap_or_else(Subscription::none);
+
+        // Animate kinetic scrolling only while it lasts
+        let frames = if self.chart.as_ref().is_some_and(|chart| chart.is_animating()) {
+            window::frames().map(Message::Frame)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([live, frames])
+    }
+
+    /// Box sizes to pick from: ATR-based, or fixed at round fractions of the last price
+    fn box_sizes(&self) -> Vec<BoxSize> {
+        let last_close = self
+            .chart
+            .as_ref()
+            .and_then(|chart| chart.candles().last())
+            .map_or(0.0, |c| c.close);
+
+        let mut sizes = vec![BoxSize::Atr(14)];
+        if last_close > 0.0 {
+            sizes.extend([0.0025, 0.005, 0.01].map(|fraction| BoxSize::Fixed(axis::nice_step(last_close * fraction))));
+        }
+        if !sizes.contains(&self.box_size) {
+            sizes.push(self.box_size);
+        }
+        sizes
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        if let Some(ref chart) = self.chart {
+            // Overlay controls on top of chart
+            let controls = row![
+                pick_list(
+                    self.symbols.as_slice(),
+                    Some(&self.symbol),
+                    Message::SymbolSelected,
+                )
+                .placeholder("Symbol"),
+                pick_list(
+                    Interval::all(),
+                    Some(self.selected_interval),
+                    Message::IntervalSelected,
+                )
+                .placeholder("Interval"),
+                pick_list(
+                    BarMode::ALL,
+                    Some(self.bar_mode),
+                    Message::BarModeSelected,
+                ),
+                pick_list(
+                    ChartType::ALL,
+                    Some(self.chart_type),
+                    Message::ChartTypeSelected,
+                ),
+                pick_list(
+                    ScaleMode::ALL,
+                    Some(self.scale_mode),
+                    Message::ScaleModeSelected,
+                ),
+                pick_list(
+                    VolumeUnit::ALL,
+                    Some(self.volume_unit),
+                    Message::VolumeUnitSelected,
+                ),
+                pick_list(
+                    DisplayTimezone::presets(),
+                    Some(self.timezone),
+                    Message::TimezoneSelected,
+                ),
+                button("↻").on_press(Message::RefreshData),
+            ]
+            .spacing(5)
+            .padding(10);
+
+            let status = if self.loading {
+                Some(text("Loading...").size(14))
+            } else {
+                self.error
+                    .as_ref()
+                    .or(self.stream_error.as_ref())
+                    .map(|error| text(format!("Error: {}", error)).size(14))
+            };
+
+            let indicators = OVERLAYS.iter().fold(row![].spacing(10).padding([0, 10]), |row, (overlay, _)| {
+                let overlay = *overlay;
+                row.push(
+                    checkbox(overlay.to_string(), self.overlays.contains(&overlay))
+                        .on_toggle(move |shown| Message::OverlayToggled(overlay, shown)),
+                )
+            });
+            let indicators = PANES.iter().fold(indicators, |row, kind| {
+                let kind = *kind;
+                row.push(
+                    checkbox(kind.to_string(), self.panes.contains(&kind))
+                        .on_toggle(move |shown| Message::PaneToggled(kind, shown)),
+                )
+            });
+            let anchor_label = match (self.anchor, self.anchoring) {
+                (Some(_), _) => "Clear AVWAP",
+                (None, true) => "Click a candle",
+                (None, false) => "Anchor VWAP",
+            };
+            let indicators = indicators.push(button(text(anchor_label).size(12)).on_press(Message::AnchorPressed));
+
+            let mut overlay_content = column![controls, indicators].spacing(5);
+
+            // Brick and box settings for the charts that are not time-based
+            if !self.chart_type.is_time_based() {
+                overlay_content = overlay_content.push(
+                    row![
+                        pick_list(
+                            self.box_sizes(),
+                            Some(self.box_size),
+                            Message::BoxSizeSelected,
+                        ),
+                        pick_list(
+                            [1, 2, 3, 4, 5],
+                            Some(self.reversal),
+                            Message::ReversalSelected,
+                        ),
+                    ]
+                    .spacing(5)
+                    .padding([0, 10]),
+                );
+            }
+
+            if let Some(status_text) = status {
+                overlay_content = overlay_content.push(status_text);
+            }
+
+            iced::widget::stack![
+                chart.view().map(Message::ChartEvent),
+                container(overlay_content)
+                    .padding(10)
+            ]
+            .into()
+        } else {
+            container(text("Loading chart...").size(20))
+                .center(iced::Length::Fill)
+                .into()
+        }
+    }
+}
+
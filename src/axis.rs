@@ -0,0 +1,363 @@
+use crate::timezone::DisplayTimezone;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Round `step` up to a "nice" value: 1, 2 or 5 times a power of ten
+pub fn nice_step(step: f64) -> f64 {
+    if !(step > 0.0 && step.is_finite()) {
+        return 1.0;
+    }
+
+    let magnitude = 10f64.powf(step.log10().floor());
+    let fraction = step / magnitude;
+
+    let nice = if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+
+    nice * magnitude
+}
+
+/// Evenly spaced ticks at nice values within `min..=max`, at most about `max_count`
+pub fn nice_ticks(min: f64, max: f64, max_count: usize) -> Vec<f64> {
+    if max <= min || max.is_nan() || min.is_nan() || max_count == 0 {
+        return Vec::new();
+    }
+
+    let step = nice_step((max - min) / max_count as f64);
+    // Tolerate rounding, so bounds on a tick keep it at any scale
+    let first = (min / step - 1e-9).ceil() as i64;
+    let last = (max / step + 1e-9).floor() as i64;
+
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Ticks for a logarithmic axis: 1, 2 and 5 times powers of ten, thinned to
+/// whole decades when they would not fit
+///
+/// Ranges spanning less than a decade look linear, so they get linear ticks.
+pub fn log_ticks(min: f64, max: f64, max_count: usize) -> Vec<f64> {
+    if !(min > 0.0 && max > min) || max_count == 0 {
+        return Vec::new();
+    }
+
+    if max / min < 10.0 {
+        return nice_ticks(min, max, max_count);
+    }
+
+    let first = min.log10().floor() as i32;
+    let last = max.log10().ceil() as i32;
+    let decades = |mantissas: &[f64]| -> Vec<f64> {
+        (first..=last)
+            .flat_map(|exp| mantissas.iter().map(move |m| m * 10f64.powi(exp)))
+            .filter(|value| *value >= min && *value <= max)
+            .collect()
+    };
+
+    let ticks = decades(&[1.0, 2.0, 5.0]);
+    if ticks.len() <= max_count {
+        return ticks;
+    }
+
+    // Still too many: keep every n-th decade
+    let ticks = decades(&[1.0]);
+    let every = ticks.len().div_ceil(max_count).max(1);
+    ticks.into_iter().step_by(every).collect()
+}
+
+/// Number of decimals needed to show multiples of `step` exactly
+pub fn decimals_for(step: f64) -> usize {
+    if !(step > 0.0 && step.is_finite()) {
+        return 0;
+    }
+
+    // Tolerate rounding, e.g. a step of 0.09999999 taken between two ticks
+    (-(step.log10() + 1e-6).floor()).max(0.0) as usize
+}
+
+/// Short label for a large quantity, e.g. 1.5K, 12.3M or 4B
+pub fn abbreviate(value: f64) -> String {
+    let (scaled, suffix) = match value.abs() {
+        v if v >= 1e9 => (value / 1e9, "B"),
+        v if v >= 1e6 => (value / 1e6, "M"),
+        v if v >= 1e3 => (value / 1e3, "K"),
+        _ => (value, ""),
+    };
+
+    // Keep about three significant digits
+    let decimals = if scaled.abs() >= 100.0 || (suffix.is_empty() && scaled.fract() == 0.0) {
+        0
+    } else if scaled.abs() >= 10.0 {
+        1
+    } else {
+        2
+    };
+    let text = format!("{:.*}", decimals, scaled);
+    let text = if text.contains('.') { text.trim_end_matches('0').trim_end_matches('.') } else { &text };
+    format!("{}{}", text, suffix)
+}
+
+/// Calendar boundary a time tick falls on, from finest to coarsest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeLevel {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+/// A tick on the time axis
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeTick {
+    pub timestamp: i64,
+    pub label: String,
+    /// Falls on a coarser boundary than the tick spacing, e.g. midnight on an hourly axis
+    pub major: bool,
+}
+
+// Tick spacings to choose from: calendar unit, count and approximate length in seconds
+const TIME_STEPS: [(TimeLevel, u32, i64); 21] = [
+    (TimeLevel::Minute, 1, 60),
+    (TimeLevel::Minute, 5, 300),
+    (TimeLevel::Minute, 15, 900),
+    (TimeLevel::Minute, 30, 1_800),
+    (TimeLevel::Hour, 1, 3_600),
+    (TimeLevel::Hour, 2, 7_200),
+    (TimeLevel::Hour, 3, 10_800),
+    (TimeLevel::Hour, 6, 21_600),
+    (TimeLevel::Hour, 12, 43_200),
+    (TimeLevel::Day, 1, 86_400),
+    (TimeLevel::Day, 2, 172_800),
+    (TimeLevel::Day, 7, 604_800),
+    (TimeLevel::Day, 14, 1_209_600),
+    (TimeLevel::Month, 1, 2_629_746),
+    (TimeLevel::Month, 3, 7_889_238),
+    (TimeLevel::Month, 6, 15_778_476),
+    (TimeLevel::Year, 1, 31_556_952),
+    (TimeLevel::Year, 2, 63_113_904),
+    (TimeLevel::Year, 5, 157_784_760),
+    (TimeLevel::Year, 10, 315_569_520),
+    (TimeLevel::Year, 50, 1_577_847_600),
+];
+
+/// Ticks between `start` and `end` (seconds) on round times, at most about `max_count`
+///
+/// Ticks land on whole minutes, hours, midnights, month starts or year starts
+/// of the wall clock in `timezone`, whichever spacing fits.
+pub fn time_ticks(start: i64, end: i64, max_count: usize, timezone: DisplayTimezone) -> Vec<TimeTick> {
+    let (Some(first), Some(last)) = (timezone.local_time(start), timezone.local_time(end)) else {
+        return Vec::new();
+    };
+
+    if end <= start || max_count == 0 {
+        return Vec::new();
+    }
+
+    let span = end - start;
+    let (unit, count, _) = TIME_STEPS
+        .iter()
+        .copied()
+        .find(|(_, _, seconds)| span / seconds < max_count as i64)
+        .unwrap_or(TIME_STEPS[TIME_STEPS.len() - 1]);
+
+    let mut ticks = Vec::new();
+    let mut push = |time: NaiveDateTime| {
+        // Skip wall-clock times that a DST change jumps over
+        let Some(timestamp) = timezone.timestamp(time) else {
+            return;
+        };
+        let level = level_of(time);
+        let major = level > unit;
+        ticks.push(TimeTick {
+            timestamp,
+            label: time.format(label_format(level, major)).to_string(),
+            major,
+        });
+    };
+
+    match unit {
+        TimeLevel::Minute | TimeLevel::Hour => {
+            // Every sub-day step divides a day, so stepping from a multiple
+            // of it within the day also hits midnight
+            let step = if unit == TimeLevel::Hour { count * 60 } else { count } as i64;
+            let minute_of_day = (first.hour() * 60 + first.minute()) as i64;
+            let offset = (step - minute_of_day % step) % step;
+            let mut time = first.with_second(0).unwrap_or(first) + Duration::minutes(offset);
+            if time < first {
+                time += Duration::minutes(step);
+            }
+
+            while time <= last {
+                push(time);
+                time += Duration::minutes(step);
+            }
+        }
+        TimeLevel::Day => {
+            let mut date = first.date();
+            if first.time() != NaiveTime::MIN {
+                date = date.succ_opt().unwrap_or(date);
+            }
+
+            while date <= last.date() {
+                // Restart the count each month so month starts are always ticks,
+                // and skip days too close to the next month start
+                let day = date.day();
+                let next_month_start = date.with_day(1).and_then(|d| d.checked_add_months(Months::new(1)));
+                let days_left = next_month_start.map_or(i64::MAX, |d| (d - date).num_days());
+                if (day - 1) % count == 0 && (day == 1 || days_left > count as i64 / 2) {
+                    push(date.and_time(NaiveTime::MIN));
+                }
+                date = match date.succ_opt() {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+        }
+        TimeLevel::Month | TimeLevel::Year => {
+            let months = if unit == TimeLevel::Year { count * 12 } else { count };
+            let Some(mut date) = NaiveDate::from_ymd_opt(first.year(), first.month(), 1) else {
+                return ticks;
+            };
+            if date.and_time(NaiveTime::MIN) < first {
+                date = date.checked_add_months(Months::new(1)).unwrap_or(date);
+            }
+
+            while date <= last.date() {
+                let month_index = date.year() as i64 * 12 + date.month0() as i64;
+                if month_index % months as i64 == 0 {
+                    push(date.and_time(NaiveTime::MIN));
+                }
+                date = match date.checked_add_months(Months::new(1)) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+        }
+    }
+
+    ticks
+}
+
+/// Coarsest calendar boundary `time` falls on
+fn level_of(time: NaiveDateTime) -> TimeLevel {
+    if time.time() != NaiveTime::MIN {
+        if time.minute() == 0 {
+            TimeLevel::Hour
+        } else {
+            TimeLevel::Minute
+        }
+    } else if time.day() != 1 {
+        TimeLevel::Day
+    } else if time.month() != 1 {
+        TimeLevel::Month
+    } else {
+        TimeLevel::Year
+    }
+}
+
+fn label_format(level: TimeLevel, major: bool) -> &'static str {
+    match level {
+        TimeLevel::Minute | TimeLevel::Hour => "%H:%M",
+        // A day change among intraday ticks needs the month to make sense
+        TimeLevel::Day if major => "%d %b",
+        TimeLevel::Day => "%d",
+        TimeLevel::Month => "%b",
+        TimeLevel::Year => "%Y",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ticks(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= e.abs() * 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn nice_ticks_of_tiny_prices() {
+        // A step of 0.000004 rounds up to 0.000005
+        let ticks = nice_ticks(0.00001, 0.00003, 5);
+        assert_ticks(&ticks, &[0.00001, 0.000015, 0.00002, 0.000025, 0.00003]);
+        assert_eq!(decimals_for(ticks[1] - ticks[0]), 6);
+    }
+
+    #[test]
+    fn log_ticks_over_decades() {
+        assert_ticks(&log_ticks(5.0, 500.0, 10), &[5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0]);
+        // Too many for 1, 2 and 5, so whole decades only
+        assert_ticks(&log_ticks(5.0, 500.0, 3), &[10.0, 100.0]);
+        // Less than a decade reads as linear
+        assert_ticks(&log_ticks(20.0, 50.0, 4), &nice_ticks(20.0, 50.0, 4));
+    }
+
+    #[test]
+    fn decimals_for_steps() {
+        assert_eq!(decimals_for(1.0), 0);
+        assert_eq!(decimals_for(0.5), 1);
+        assert_eq!(decimals_for(0.005), 3);
+        // A step taken between two ticks can come out a little short
+        assert_eq!(decimals_for(0.1 - 1e-9), 1);
+        assert_eq!(decimals_for(0.0), 0);
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .map(|time| time.and_utc().timestamp())
+            .unwrap()
+    }
+
+    fn labels(ticks: &[TimeTick]) -> Vec<&str> {
+        ticks.iter().map(|tick| tick.label.as_str()).collect()
+    }
+
+    fn majors(ticks: &[TimeTick]) -> Vec<&str> {
+        ticks.iter().filter(|tick| tick.major).map(|tick| tick.label.as_str()).collect()
+    }
+
+    #[test]
+    fn hourly_ticks_mark_midnight() {
+        let ticks = time_ticks(utc(2024, 1, 15, 20, 30), utc(2024, 1, 16, 4, 0), 10, DisplayTimezone::Utc);
+
+        assert_eq!(labels(&ticks), ["21:00", "22:00", "23:00", "16 Jan", "01:00", "02:00", "03:00", "04:00"]);
+        assert_eq!(majors(&ticks), ["16 Jan"]);
+        assert_eq!(ticks[3].timestamp, utc(2024, 1, 16, 0, 0));
+    }
+
+    #[test]
+    fn weekly_ticks_restart_each_month() {
+        let ticks = time_ticks(utc(2024, 1, 10, 0, 0), utc(2024, 3, 5, 0, 0), 10, DisplayTimezone::Utc);
+
+        // The 29th is too close to the next month start to get a tick
+        assert_eq!(labels(&ticks), ["15", "22", "Feb", "08", "15", "22", "Mar"]);
+        assert_eq!(majors(&ticks), ["Feb", "Mar"]);
+    }
+
+    #[test]
+    fn quarterly_ticks_mark_the_year() {
+        let ticks = time_ticks(utc(2023, 6, 1, 0, 0), utc(2025, 2, 1, 0, 0), 8, DisplayTimezone::Utc);
+
+        assert_eq!(labels(&ticks), ["Jul", "Oct", "2024", "Apr", "Jul", "Oct", "2025"]);
+        assert_eq!(majors(&ticks), ["2024", "2025"]);
+    }
+
+    #[test]
+    fn ticks_skip_times_a_dst_change_jumps_over() {
+        let new_york = DisplayTimezone::named("America/New_York").unwrap();
+        // Midnight EST to 05:00 EDT, clocks went from 02:00 to 03:00
+        let ticks = time_ticks(utc(2024, 3, 10, 5, 0), utc(2024, 3, 10, 9, 0), 5, new_york);
+
+        assert_eq!(labels(&ticks), ["10 Mar", "01:00", "03:00", "04:00", "05:00"]);
+        assert_eq!(ticks[2].timestamp - ticks[1].timestamp, 3600);
+    }
+}
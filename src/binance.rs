@@ -1,79 +1,20 @@
 use crate::candlestick::Candle;
-use std::fmt;
-
-/// Binance timeframe/interval options
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Interval {
-    OneMinute,
-    FiveMinutes,
-    FifteenMinutes,
-    ThirtyMinutes,
-    OneHour,
-    FourHours,
-    OneDay,
-}
-
-impl Interval {
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            Interval::OneMinute => "1m",
-            Interval::FiveMinutes => "5m",
-            Interval::FifteenMinutes => "15m",
-            Interval::ThirtyMinutes => "30m",
-            Interval::OneHour => "1h",
-            Interval::FourHours => "4h",
-            Interval::OneDay => "1d",
-        }
-    }
+use crate::source::{DataSource, Interval, SymbolInfo};
+use crate::trades::Trade;
+use iced::futures::channel::mpsc;
+use iced::futures::executor::block_on;
+use iced::futures::future::BoxFuture;
+use iced::futures::{SinkExt, StreamExt};
+use iced::{stream, Subscription};
+use serde::Deserialize;
+use std::thread;
+use std::time::{Duration, Instant};
 
-    pub fn to_minutes(&self) -> i64 {
-        match self {
-            Interval::OneMinute => 1,
-            Interval::FiveMinutes => 5,
-            Interval::FifteenMinutes => 15,
-            Interval::ThirtyMinutes => 30,
-            Interval::OneHour => 60,
-            Interval::FourHours => 240,
-            Interval::OneDay => 1440,
-        }
-    }
+/// Default Binance market data WebSocket endpoint
+pub const DEFAULT_STREAM_ENDPOINT: &str = "wss://stream.binance.com:9443/ws";
 
-    pub fn all() -> Vec<Interval> {
-        vec![
-            Interval::OneMinute,
-            Interval::FiveMinutes,
-            Interval::FifteenMinutes,
-            Interval::ThirtyMinutes,
-            Interval::OneHour,
-            Interval::FourHours,
-            Interval::OneDay,
-        ]
-    }
-
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            Interval::OneMinute => "1 Minute",
-            Interval::FiveMinutes => "5 Minutes",
-            Interval::FifteenMinutes => "15 Minutes",
-            Interval::ThirtyMinutes => "30 Minutes",
-            Interval::OneHour => "1 Hour",
-            Interval::FourHours => "4 Hours",
-            Interval::OneDay => "1 Day",
-        }
-    }
-}
-
-impl Default for Interval {
-    fn default() -> Self {
-        Interval::OneHour
-    }
-}
-
-impl fmt::Display for Interval {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.display_name())
-    }
-}
+/// Delay before reconnecting after the stream drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
 
 // Binance kline response format: array of arrays
 // [open_time, open, high, low, close, volume, close_time, quote_volume, trades, taker_buy_base, taker_buy_quote, unused]
@@ -92,14 +33,139 @@ type BinanceKline = (
     String, // unused
 );
 
+/// Market data from the Binance spot API
+pub struct BinanceSource {
+    stream_endpoint: String,
+}
+
+impl BinanceSource {
+    pub fn new() -> Self {
+        Self {
+            stream_endpoint: DEFAULT_STREAM_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Use a different WebSocket base URL for live klines and trades
+    pub fn with_stream_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.stream_endpoint = endpoint.into();
+        self
+    }
+}
+
+impl Default for BinanceSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataSource for BinanceSource {
+    fn name(&self) -> &str {
+        "Binance"
+    }
+
+    fn symbols(&self) -> BoxFuture<'static, Result<Vec<SymbolInfo>, String>> {
+        Box::pin(fetch_symbols())
+    }
+
+    fn klines(&self, symbol: &str, interval: Interval, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>> {
+        let symbol = symbol.to_string();
+        Box::pin(async move { fetch_klines(&symbol, interval, limit, None).await })
+    }
+
+    fn klines_before(&self, symbol: &str, interval: Interval, end_time: i64, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>> {
+        let symbol = symbol.to_string();
+        // endTime is inclusive and in milliseconds
+        let end_time_ms = end_time * 1000 - 1;
+        Box::pin(async move { fetch_klines(&symbol, interval, limit, Some(end_time_ms)).await })
+    }
+
+    fn live(&self, symbol: &str, interval: Interval) -> Option<Subscription<Result<Candle, String>>> {
+        Some(kline_stream(&self.stream_endpoint, symbol, interval))
+    }
+
+    fn trades(&self, symbol: &str, limit: u32) -> BoxFuture<'static, Result<Vec<Trade>, String>> {
+        let symbol = symbol.to_string();
+        Box::pin(async move { fetch_agg_trades(&symbol, limit).await })
+    }
+
+    fn live_trades(&self, symbol: &str) -> Option<Subscription<Result<Trade, String>>> {
+        Some(agg_trade_stream(&self.stream_endpoint, symbol))
+    }
+}
+
+#[derive(Deserialize)]
+struct ExchangeInfo {
+    symbols: Vec<ExchangeSymbol>,
+}
+
+#[derive(Deserialize)]
+struct ExchangeSymbol {
+    symbol: String,
+    filters: Vec<SymbolFilter>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SymbolFilter {
+    filter_type: String,
+    tick_size: Option<String>, // Only set on PRICE_FILTER, e.g. "0.01000000"
+}
+
+impl ExchangeSymbol {
+    fn to_info(&self) -> SymbolInfo {
+        let precision = self
+            .filters
+            .iter()
+            .find(|f| f.filter_type == "PRICE_FILTER")
+            .and_then(|f| f.tick_size.as_deref())
+            .map_or(2, tick_size_decimals);
+
+        SymbolInfo::new(self.symbol.clone(), precision)
+    }
+}
+
+/// Decimals in a tick size such as "0.00010000"
+fn tick_size_decimals(tick_size: &str) -> usize {
+    tick_size
+        .trim_end_matches('0')
+        .split_once('.')
+        .map_or(0, |(_, decimals)| decimals.len())
+}
+
+/// Fetch the symbols currently trading on Binance, with their price precision
+pub async fn fetch_symbols() -> Result<Vec<SymbolInfo>, String> {
+    let url = "https://api.binance.com/api/v3/exchangeInfo?symbolStatus=TRADING";
+
+    let response = reqwest::blocking::get(url)
+        .map_err(|e| format!("Failed to fetch symbols: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Binance API error: {}", response.status()));
+    }
+
+    let info: ExchangeInfo = response
+        .json()
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let mut symbols: Vec<SymbolInfo> = info.symbols.iter().map(ExchangeSymbol::to_info).collect();
+    symbols.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(symbols)
+}
+
 /// Fetch candlestick data from Binance API
-pub async fn fetch_klines(symbol: &str, interval: Interval, limit: u32) -> Result<Vec<Candle>, String> {
-    let url = format!(
+///
+/// With `end_time` (ms) set, returns the `limit` candles opening at or before it.
+pub async fn fetch_klines(symbol: &str, interval: Interval, limit: u32, end_time: Option<i64>) -> Result<Vec<Candle>, String> {
+    let mut url = format!(
         "https://api.binance.com/api/v3/klines?symbol={}&interval={}&limit={}",
         symbol,
         interval.as_str(),
         limit
     );
+    if let Some(end_time) = end_time {
+        url.push_str(&format!("&endTime={}", end_time));
+    }
 
     // Use blocking reqwest client since iced has its own runtime
     let response = reqwest::blocking::get(&url)
@@ -123,10 +189,213 @@ pub async fn fetch_klines(symbol: &str, interval: Interval, limit: u32) -> Resul
             let low = kline.3.parse::<f64>().ok()?;
             let close = kline.4.parse::<f64>().ok()?;
             let volume = kline.5.parse::<f64>().ok()?;
+            let quote_volume = kline.7.parse::<f64>().ok()?;
 
-            Some(Candle::new(timestamp, open, high, low, close, volume))
+            Some(Candle::new(timestamp, open, high, low, close, volume).with_quote_volume(quote_volume))
         })
         .collect();
 
     Ok(candles)
 }
+
+// Binance kline stream event, only the fields we use
+// {"e":"kline","E":..,"s":"BTCUSDT","k":{"t":..,"o":"..","h":"..","l":"..","c":"..","v":"..","x":false,..}}
+#[derive(Deserialize)]
+struct KlineEvent {
+    k: StreamKline,
+}
+
+#[derive(Deserialize)]
+struct StreamKline {
+    t: i64,    // open time (ms)
+    o: String, // open
+    h: String, // high
+    l: String, // low
+    c: String, // close
+    v: String, // volume
+    q: String, // quote asset volume
+}
+
+impl StreamKline {
+    fn to_candle(&self) -> Option<Candle> {
+        Some(Candle::new(
+            self.t / 1000,
+            self.o.parse().ok()?,
+            self.h.parse().ok()?,
+            self.l.parse().ok()?,
+            self.c.parse().ok()?,
+            self.v.parse().ok()?,
+        )
+        .with_quote_volume(self.q.parse().ok()?))
+    }
+}
+
+/// Subscribe to the live `<symbol>@kline_<interval>` stream.
+///
+/// Each item is the current state of the forming candle; a new open time means
+/// the previous candle has closed. `endpoint` is the WebSocket base URL, e.g.
+/// [`DEFAULT_STREAM_ENDPOINT`] or a local stand-in such as `ws://127.0.0.1:9001`.
+/// The connection is retried every few seconds until the subscription is dropped,
+/// and the candles that closed while it was down are fetched again on reconnect.
+pub fn kline_stream(endpoint: &str, symbol: &str, interval: Interval) -> Subscription<Result<Candle, String>> {
+    let url = format!(
+        "{}/{}@kline_{}",
+        endpoint.trim_end_matches('/'),
+        symbol.to_lowercase(),
+        interval.as_str()
+    );
+
+    let symbol = symbol.to_string();
+    let backfill = move |gap: Duration| {
+        // The candle forming at the disconnect and every one opened since
+        let limit = (gap.as_secs() / (interval.to_minutes() as u64 * 60) + 2).min(1000) as u32;
+        block_on(fetch_klines(&symbol, interval, limit, None))
+    };
+
+    socket_stream(
+        url,
+        |text| {
+            serde_json::from_str::<KlineEvent>(text)
+                .ok()
+                .and_then(|event| event.k.to_candle())
+        },
+        Some(Box::new(backfill)),
+    )
+}
+
+// Binance aggregate trade, the same fields in REST responses and stream events
+// {"a":..,"p":"..","q":"..","f":..,"l":..,"T":..,"m":true,"M":true}
+#[derive(Deserialize)]
+struct AggTrade {
+    a: u64,    // aggregate trade id
+    p: String, // price
+    q: String, // quantity
+    #[serde(rename = "T")]
+    time: i64, // trade time (ms)
+}
+
+impl AggTrade {
+    fn to_trade(&self) -> Option<Trade> {
+        Some(Trade::new(self.a, self.time, self.p.parse().ok()?, self.q.parse().ok()?))
+    }
+}
+
+/// Fetch the most recent `limit` aggregate trades (at most 1000), oldest first
+pub async fn fetch_agg_trades(symbol: &str, limit: u32) -> Result<Vec<Trade>, String> {
+    let url = format!(
+        "https://api.binance.com/api/v3/aggTrades?symbol={}&limit={}",
+        symbol,
+        limit.min(1000)
+    );
+
+    let response = reqwest::blocking::get(&url)
+        .map_err(|e| format!("Failed to fetch trades: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Binance API error: {}", response.status()));
+    }
+
+    let trades: Vec<AggTrade> = response
+        .json()
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    Ok(trades.iter().filter_map(AggTrade::to_trade).collect())
+}
+
+/// Subscribe to the live `<symbol>@aggTrade` stream, see [`kline_stream`]
+pub fn agg_trade_stream(endpoint: &str, symbol: &str) -> Subscription<Result<Trade, String>> {
+    let url = format!("{}/{}@aggTrade", endpoint.trim_end_matches('/'), symbol.to_lowercase());
+
+    socket_stream(
+        url,
+        |text| {
+            serde_json::from_str::<AggTrade>(text)
+                .ok()
+                .and_then(|trade| trade.to_trade())
+        },
+        None,
+    )
+}
+
+/// Fetches what was missed over a disconnect of the given length, oldest first
+type Backfill<T> = Box<dyn Fn(Duration) -> Result<Vec<T>, String> + Send>;
+
+/// Subscription yielding every text message of the socket at `url` that `parse`
+/// accepts, after what `backfill` fetches on each reconnect
+fn socket_stream<T: Send + 'static>(
+    url: String,
+    parse: fn(&str) -> Option<T>,
+    backfill: Option<Backfill<T>>,
+) -> Subscription<Result<T, String>> {
+    Subscription::run_with_id(
+        url.clone(),
+        stream::channel(100, move |mut output| async move {
+            // tungstenite is blocking, so the socket lives on its own thread
+            // and hands updates back through a channel
+            let (sender, mut receiver) = mpsc::unbounded();
+            thread::spawn(move || run_socket(&url, sender, parse, backfill));
+
+            while let Some(update) = receiver.next().await {
+                if output.send(update).await.is_err() {
+                    break;
+                }
+            }
+        }),
+    )
+}
+
+/// Read messages from the socket until the receiving side goes away
+fn run_socket<T>(
+    url: &str,
+    sender: mpsc::UnboundedSender<Result<T, String>>,
+    parse: fn(&str) -> Option<T>,
+    backfill: Option<Backfill<T>>,
+) {
+    // When the last message arrived, the start of the gap to fill on reconnect
+    let mut last_message: Option<Instant> = None;
+
+    while !sender.is_closed() {
+        let mut socket = match tungstenite::connect(url) {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                let _ = sender.unbounded_send(Err(format!("Failed to connect to stream: {}", e)));
+                thread::sleep(RECONNECT_DELAY);
+                continue;
+            }
+        };
+
+        if let (Some(backfill), Some(since)) = (&backfill, last_message) {
+            let missed = match backfill(since.elapsed()) {
+                Ok(updates) => updates.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            for update in missed {
+                if sender.unbounded_send(update).is_err() {
+                    return;
+                }
+            }
+        }
+
+        loop {
+            let text = match socket.read() {
+                Ok(tungstenite::Message::Text(text)) => text,
+                Ok(tungstenite::Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    let _ = sender.unbounded_send(Err(format!("Stream error: {}", e)));
+                    break;
+                }
+            };
+
+            let Some(update) = parse(&text) else {
+                continue;
+            };
+            last_message = Some(Instant::now());
+            if sender.unbounded_send(Ok(update)).is_err() {
+                return;
+            }
+        }
+
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
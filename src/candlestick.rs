@@ -1,14 +1,66 @@
-use iced::widget::canvas::{self, Canvas, Event, Frame, Geometry, Path, Stroke, Text};
-use iced::{Color, Element, Point, Rectangle, Size, Theme};
+use iced::widget::canvas::{self, path, Canvas, Event, Frame, Geometry, LineDash, Path, Stroke, Text};
+use iced::font::Weight;
+use iced::{Color, Element, Font, Length, Point, Rectangle, Size, Theme, Vector};
 use iced::mouse::{Cursor, ScrollDelta};
+use iced::keyboard::{self, key::Named, Key, Modifiers};
 use iced::alignment::{Horizontal, Vertical};
 use iced::event::Status;
-use chrono::DateTime;
+use iced::time::Instant;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use crate::axis;
+use crate::indicator::{self, Overlay, Tracker};
+use crate::pane::{Layout, Pane, PaneButton, PaneId, PaneKind, DEFAULT_PANE_RATIO};
+use crate::timezone::DisplayTimezone;
+use crate::transform::{self, BoxSize};
+use crate::viewport::{ScaleMode, Viewport};
 
+// Margins around the plotting area, the right one holds the price axis
+const LEFT_MARGIN: f32 = 10.0;
+const RIGHT_MARGIN: f32 = 60.0;
+const TOP_MARGIN: f32 = 10.0;
+const BOTTOM_MARGIN: f32 = 30.0;
+
+const BOLD: Font = Font {
+    weight: Weight::Bold,
+    ..Font::DEFAULT
+};
+
+// Two clicks closer than this count as a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+// A press released within this many pixels of where it started is a click
+const CLICK_SLOP: f32 = 3.0;
+
+// A drag released after holding still this long does not fling
+const FLING_TIMEOUT: Duration = Duration::from_millis(50);
+
+// Kinetic scrolling loses this fraction of its speed per second, and stops
+// below the minimum speed (bars per second)
+const FLING_FRICTION: f64 = 0.95;
+const MIN_FLING_SPEED: f64 = 0.5;
+
+// Zoom speed: a mouse wheel scrolls in lines, a trackpad in many small pixel steps
+const ZOOM_PER_LINE: f64 = 0.1;
+const ZOOM_PER_PIXEL: f64 = 0.002;
+
+// Zooming in stops at this many bars across the chart
+const MIN_VISIBLE_BARS: f64 = 10.0;
+
+/// Events the host may want to react to, the chart handles zoom and pan itself
 #[derive(Debug, Clone)]
 pub enum ChartMessage {
-    Zoom(f32),
-    Pan(f32), // Drag delta in pixels
+    /// Bar positions now shown, see [`Viewport`]
+    ViewportChanged { start: f64, end: f64 },
+    /// A candle was clicked without dragging
+    CandleClicked(Candle),
+    NeedMoreHistory, // Scrolled past the oldest loaded candle
+    /// The close button of a pane was clicked, see [`CandlestickChart::remove_pane`]
+    PaneCloseRequested(PaneId),
+    /// A move button of a pane was clicked, see [`CandlestickChart::move_pane`]
+    PaneMoveRequested { pane: PaneId, up: bool },
 }
 
 /// Represents a single candlestick (OHLC data)
@@ -20,9 +72,12 @@ pub struct Candle {
     pub low: f64,
     pub close: f64,
     pub volume: f64,
+    pub quote_volume: f64, // Volume in the pricing asset
 }
 
 impl Candle {
+    /// Candle whose quote volume is estimated at the typical price, see
+    /// [`Candle::with_quote_volume`] for sources that give it
     pub fn new(timestamp: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> Self {
         Self {
             timestamp,
@@ -31,450 +86,2288 @@ impl Candle {
             low,
             close,
             volume,
+            quote_volume: volume * (high + low + close) / 3.0,
         }
     }
 
+    /// Set the volume in the pricing asset, as reported by the source
+    pub fn with_quote_volume(mut self, quote_volume: f64) -> Self {
+        self.quote_volume = quote_volume;
+        self
+    }
+
     pub fn is_bullish(&self) -> bool {
         self.close >= self.open
     }
+
+    /// Average of the high, low and close
+    pub fn typical_price(&self) -> f64 {
+        (self.high + self.low + self.close) / 3.0
+    }
+}
+
+/// Asset the volume is counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VolumeUnit {
+    /// The traded asset, e.g. BTC for BTCUSDT
+    #[default]
+    Base,
+    /// The pricing asset, e.g. USDT for BTCUSDT
+    Quote,
+}
+
+impl VolumeUnit {
+    pub const ALL: [VolumeUnit; 2] = [VolumeUnit::Base, VolumeUnit::Quote];
+
+    /// Volume of `candle` in this unit
+    pub fn of(self, candle: &Candle) -> f64 {
+        match self {
+            VolumeUnit::Base => candle.volume,
+            VolumeUnit::Quote => candle.quote_volume,
+        }
+    }
+}
+
+impl fmt::Display for VolumeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VolumeUnit::Base => "Base Volume",
+            VolumeUnit::Quote => "Quote Volume",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Colors used to draw the chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartStyle {
+    pub background: Color,
+    pub grid: Color,
+    pub border: Color,
+    pub text: Color,
+    pub bullish: Color,
+    pub bearish: Color,
+    pub crosshair: Color,
+    /// Close-price line of the line, area and baseline charts, and the main
+    /// line of indicators in panes
+    pub line: Color,
+    /// Second line of indicators in panes, such as the MACD signal
+    pub signal: Color,
+}
+
+impl Default for ChartStyle {
+    fn default() -> Self {
+        Self {
+            background: Color::BLACK,
+            grid: Color::from_rgb(0.2, 0.2, 0.2),
+            border: Color::from_rgb(0.4, 0.4, 0.4),
+            text: Color::from_rgb(0.8, 0.8, 0.8),
+            bullish: Color::from_rgb(0.0, 0.8, 0.0),
+            bearish: Color::from_rgb(0.8, 0.0, 0.0),
+            crosshair: Color::from_rgba(0.8, 0.8, 0.8, 0.5),
+            line: Color::from_rgb(0.2, 0.6, 1.0),
+            signal: Color::from_rgb(1.0, 0.6, 0.2),
+        }
+    }
+}
+
+/// How the candles are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ChartType {
+    #[default]
+    Candles,
+    /// Rising candles outlined, colored by the change from the previous close
+    HollowCandles,
+    OhlcBars,
+    /// Close prices joined by a line
+    Line,
+    /// Line filled down to the bottom of the chart
+    Area,
+    /// Line filled towards a reference price, colored by the side it is on
+    Baseline,
+    /// Candles averaged with their predecessors to show the trend
+    HeikinAshi,
+    /// Equal bricks added as the close moves a box, regardless of time
+    Renko,
+    /// Lines turning when the close retraces by the reversal amount
+    Kagi,
+    /// Columns of rising X and falling O boxes
+    PointAndFigure,
+}
+
+impl ChartType {
+    pub const ALL: [ChartType; 10] = [
+        ChartType::Candles,
+        ChartType::HollowCandles,
+        ChartType::OhlcBars,
+        ChartType::Line,
+        ChartType::Area,
+        ChartType::Baseline,
+        ChartType::HeikinAshi,
+        ChartType::Renko,
+        ChartType::Kagi,
+        ChartType::PointAndFigure,
+    ];
+
+    /// Whether bars stand for equal time intervals, rather than price moves
+    pub fn is_time_based(self) -> bool {
+        !matches!(self, ChartType::Renko | ChartType::Kagi | ChartType::PointAndFigure)
+    }
+
+    /// Whether bars are computed from the candles rather than drawn as they are
+    pub fn is_derived(self) -> bool {
+        matches!(self, ChartType::HeikinAshi | ChartType::Renko | ChartType::Kagi | ChartType::PointAndFigure)
+    }
+
+    /// Usual reversal in boxes for the chart types that have one
+    pub fn default_reversal(self) -> usize {
+        match self {
+            ChartType::PointAndFigure => 3,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for ChartType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ChartType::Candles => "Candles",
+            ChartType::HollowCandles => "Hollow Candles",
+            ChartType::OhlcBars => "Bars",
+            ChartType::Line => "Line",
+            ChartType::Area => "Area",
+            ChartType::Baseline => "Baseline",
+            ChartType::HeikinAshi => "Heikin-Ashi",
+            ChartType::Renko => "Renko",
+            ChartType::Kagi => "Kagi",
+            ChartType::PointAndFigure => "Point & Figure",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 /// Candlestick chart widget
+///
+/// Owns the whole series and which part of it is shown. Zoom, pan and kinetic
+/// scrolling are handled by the chart; the host keeps it alive across updates,
+/// feeds it new candles and animates it with [`CandlestickChart::tick`].
+/// Clicking the chart gives it keyboard focus for navigating with the keys:
+///
+/// ```no_run
+/// # use iced_candlestick::{Candle, CandlestickChart, ChartStyle};
+/// # let candles: Vec<Candle> = Vec::new();
+/// let chart = CandlestickChart::new(candles, 60)
+///     .style(ChartStyle::default())
+///     .height(400);
+/// ```
 pub struct CandlestickChart {
     candles: Vec<Candle>,
+    derived: Vec<Candle>, // Drawn in place of the candles by derived chart types
+    derived_ends: Vec<i64>, // Last candle time of each derived bar, if not time-based
     interval_minutes: i64, // Interval in minutes (1, 5, 15, 60, 240, 1440)
+    navigation: Cell<Navigation>,
+    future_margin: Duration, // Empty time that can be shown past the newest candle
+    loading_history: bool, // Older candles are being fetched
+    chart_type: ChartType,
+    baseline: Option<f64>, // Reference price of the baseline chart
+    box_size: BoxSize,
+    box_price: Option<f64>, // Box size in price, resolved once per series load
+    reversal: Option<usize>, // Boxes to turn, the chart type's default if unset
+    scale_mode: ScaleMode,
+    overlays: Vec<OverlayLines>,
+    panes: Vec<Pane>, // Below the prices, top to bottom
+    pane_lines: HashMap<PaneId, PaneLines>,
+    next_pane_id: usize,
+    volume_unit: VolumeUnit,
+    volume_ma: Option<usize>, // Period of the volume moving average
+    volume_average: PaneLines, // The volume moving average, no lines without one
+    price_precision: usize, // Decimals shown for prices
+    timezone: DisplayTimezone,
+    style: ChartStyle,
+    width: Length,
+    height: Length,
+    background_cache: canvas::Cache, // Background, grid and axes
+    series_cache: canvas::Cache,     // Volume bars and candles
+    overlay_cache: canvas::Cache,    // Drawn over the series
+    drawn_viewport: Cell<Option<Viewport>>, // Viewport the cached layers were drawn for
+}
+
+/// An indicator drawn over the prices, with its values for every bar of the series
+#[derive(Debug, Clone)]
+struct OverlayLines {
+    overlay: Overlay,
+    color: Color,
+    lines: Vec<Vec<Option<f64>>>,
+    tracker: Tracker, // Follows the last candle
+}
+
+/// Values of the indicator of a pane for every bar of the series
+#[derive(Debug, Clone)]
+struct PaneLines {
+    lines: Vec<Vec<Option<f64>>>,
+    tracker: Tracker, // Follows the last candle
+}
+
+/// Which bars are shown, kept relative to the newest candle so that appending
+/// and prepending candles leave the view where it is
+#[derive(Debug, Clone, Copy)]
+struct Navigation {
+    bars: f64,   // Bars across the chart, may be fractional
+    offset: f64, // Bars between the newest candle and the right edge
+    velocity: f64, // Kinetic scrolling speed in bars per second, towards older candles
+    last_frame: Option<Instant>,
 }
 
 impl CandlestickChart {
+    /// Create a chart of `candles` (oldest first) spaced `interval_minutes` apart,
+    /// showing the latest 100
+    ///
+    /// Use 0 for bars without a fixed interval, such as range or volume bars.
     pub fn new(candles: Vec<Candle>, interval_minutes: i64) -> Self {
-        Self { candles, interval_minutes }
+        let mut chart = Self {
+            candles,
+            derived: Vec::new(),
+            derived_ends: Vec::new(),
+            interval_minutes,
+            navigation: Cell::new(Navigation {
+                bars: 100.0,
+                offset: 0.0,
+                velocity: 0.0,
+                last_frame: None,
+            }),
+            future_margin: Duration::ZERO,
+            loading_history: false,
+            chart_type: ChartType::default(),
+            baseline: None,
+            box_size: BoxSize::default(),
+            box_price: None,
+            reversal: None,
+            scale_mode: ScaleMode::default(),
+            overlays: Vec::new(),
+            panes: Vec::new(),
+            pane_lines: HashMap::new(),
+            next_pane_id: 0,
+            volume_unit: VolumeUnit::default(),
+            volume_ma: Some(20),
+            volume_average: PaneLines {
+                lines: Vec::new(),
+                tracker: Tracker::Window(0),
+            },
+            price_precision: 2,
+            timezone: DisplayTimezone::default(),
+            style: ChartStyle::default(),
+            width: Length::Fill,
+            height: Length::Fill,
+            background_cache: canvas::Cache::new(),
+            series_cache: canvas::Cache::new(),
+            overlay_cache: canvas::Cache::new(),
+            drawn_viewport: Cell::new(None),
+        };
+        chart.average_volume();
+        chart
     }
 
-    pub fn view(&self) -> Element<'_, ChartMessage> {
-        Canvas::new(self)
-            .width(iced::Length::Fill)
-            .height(iced::Length::Fill)
-            .into()
+    /// Set how many bars are shown across the chart initially
+    pub fn visible_bars(self, bars: f64) -> Self {
+        self.navigation.set(Navigation {
+            bars: bars.max(MIN_VISIBLE_BARS),
+            ..self.navigation.get()
+        });
+        self
     }
-}
 
-#[derive(Default)]
-pub struct ChartState {
-    dragging: bool,
-    last_x: f32,
-    cursor_position: Option<Point>,
-}
+    /// Leave room for `margin` of empty time right of the newest candle, where
+    /// indicators projected ahead such as the Ichimoku cloud are drawn
+    ///
+    /// The margin spans as many bars as fit in it, see
+    /// [`CandlestickChart::bar_duration`].
+    pub fn future_margin(mut self, margin: Duration) -> Self {
+        self.set_future_margin(margin);
+        self
+    }
 
-impl canvas::Program<ChartMessage> for CandlestickChart {
-    type State = ChartState;
+    /// Set how the candles are drawn
+    pub fn chart_type(mut self, chart_type: ChartType) -> Self {
+        self.set_chart_type(chart_type);
+        self
+    }
 
-    fn update(
-        &self,
-        state: &mut Self::State,
-        event: Event,
-        _bounds: Rectangle,
-        cursor: Cursor,
-    ) -> (Status, Option<ChartMessage>) {
-        match event {
-            Event::Mouse(mouse_event) => match mouse_event {
-                iced::mouse::Event::WheelScrolled { delta } => {
-                    match delta {
-                        ScrollDelta::Lines { y, .. } | ScrollDelta::Pixels { y, .. } => {
-                            (Status::Captured, Some(ChartMessage::Zoom(y)))
-                        }
-                    }
-                }
-                iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
-                    if let Some(position) = cursor.position() {
-                        state.dragging = true;
-                        state.last_x = position.x;
-                        (Status::Captured, None)
-                    } else {
-                        (Status::Ignored, None)
-                    }
-                }
-                iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left) => {
-                    state.dragging = false;
-                    (Status::Captured, None)
-                }
-                iced::mouse::Event::CursorMoved { .. } => {
-                    state.cursor_position = cursor.position();
+    /// Set the reference price of [`ChartType::Baseline`], the first visible
+    /// close by default
+    pub fn baseline(mut self, price: f64) -> Self {
+        self.baseline = Some(price);
+        self
+    }
 
-                    if state.dragging {
-                        if let Some(position) = cursor.position() {
-                            let delta = position.x - state.last_x;
-                            state.last_x = position.x;
-                            (Status::Captured, Some(ChartMessage::Pan(delta)))
-                        } else {
-                            (Status::Ignored, None)
-                        }
-                    } else {
-                        (Status::Ignored, None)
-                    }
-                }
-                iced::mouse::Event::CursorLeft => {
-                    state.cursor_position = None;
-                    (Status::Ignored, None)
-                }
-                _ => (Status::Ignored, None),
-            },
-            _ => (Status::Ignored, None),
-        }
+    /// Set the box size of the Renko, Kagi and Point-and-Figure charts
+    pub fn box_size(mut self, box_size: BoxSize) -> Self {
+        self.set_box_size(box_size);
+        self
     }
 
-    fn draw(
-        &self,
-        state: &Self::State,
-        renderer: &iced::Renderer,
-        _theme: &Theme,
-        bounds: Rectangle,
-        _cursor: Cursor,
-    ) -> Vec<Geometry> {
-        let mut frame = Frame::new(renderer, bounds.size());
+    /// Set how many boxes the price must move back for Renko, Kagi and
+    /// Point-and-Figure charts to turn, see [`ChartType::default_reversal`]
+    pub fn reversal(mut self, boxes: usize) -> Self {
+        self.set_reversal(boxes);
+        self
+    }
 
-        if self.candles.is_empty() {
-            return vec![frame.into_geometry()];
-        }
+    /// Draw an indicator over the prices in `color`
+    pub fn overlay(mut self, overlay: Overlay, color: Color) -> Self {
+        self.add_overlay(overlay, color);
+        self
+    }
 
-        // Draw black background
-        let background = Path::rectangle(Point::ORIGIN, bounds.size());
-        frame.fill(&background, Color::BLACK);
+    /// Add a pane below the prices, under the existing ones
+    pub fn pane(mut self, kind: PaneKind) -> Self {
+        self.add_pane(kind);
+        self
+    }
+
+    /// Show volume in a pane below the prices rather than behind them
+    pub fn volume_pane(mut self, enabled: bool) -> Self {
+        self.set_volume_pane(enabled);
+        self
+    }
 
-        // Define margins for axes
-        let left_margin = 10.0;
-        let bottom_margin = 30.0;
-        let top_margin = 10.0;
-        let right_margin = 60.0;
+    /// Set the asset volume is counted in
+    pub fn volume_unit(mut self, unit: VolumeUnit) -> Self {
+        self.set_volume_unit(unit);
+        self
+    }
 
-        // Calculate chart area
-        let chart_width = bounds.width - left_margin - right_margin;
-        let chart_height = bounds.height - top_margin - bottom_margin;
-        let chart_x = left_margin;
-        let chart_y = top_margin;
+    /// Set the period of the moving average drawn in the volume pane, `None`
+    /// to hide it
+    pub fn volume_ma(mut self, period: Option<usize>) -> Self {
+        self.volume_ma = period.filter(|period| *period > 0);
+        self.average_volume();
+        self
+    }
 
-        // Calculate price range
-        let mut min_price = f64::MAX;
-        let mut max_price = f64::MIN;
+    /// Set how prices are mapped onto the y-axis
+    pub fn scale_mode(mut self, mode: ScaleMode) -> Self {
+        self.set_scale_mode(mode);
+        self
+    }
 
-        for candle in &self.candles {
-            min_price = min_price.min(candle.low);
-            max_price = max_price.max(candle.high);
-        }
+    /// Set the number of decimals shown for prices, usually derived from
+    /// the symbol's tick size
+    pub fn price_precision(mut self, decimals: usize) -> Self {
+        self.set_price_precision(decimals);
+        self
+    }
 
-        // Add some padding to the price range
-        let price_range = max_price - min_price;
-        let padding = price_range * 0.1;
-        min_price -= padding;
-        max_price += padding;
+    /// Set the timezone used for the time axis, crosshair and info box
+    pub fn timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.set_timezone(timezone);
+        self
+    }
 
-        let price_span = max_price - min_price;
+    /// Set the colors of the chart
+    pub fn style(mut self, style: ChartStyle) -> Self {
+        self.style = style;
+        self
+    }
 
-        // Draw grid lines and Y-axis labels (prices)
-        let num_price_lines = 5;
-        let grid_color = Color::from_rgb(0.2, 0.2, 0.2);
-        let text_color = Color::from_rgb(0.8, 0.8, 0.8);
+    /// Set the width of the chart, `Fill` by default
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
 
-        for i in 0..=num_price_lines {
-            let ratio = i as f32 / num_price_lines as f32;
-            let y = chart_y + chart_height - (ratio * chart_height);
-            let price = min_price + (ratio as f64 * price_span);
+    /// Set the height of the chart, `Fill` by default
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
 
-            // Draw grid line
-            let grid_line = Path::line(
-                Point::new(chart_x, y),
-                Point::new(chart_x + chart_width, y),
-            );
-            frame.stroke(
-                &grid_line,
-                Stroke::default().with_width(1.0).with_color(grid_color),
-            );
+    /// The whole series, oldest first
+    pub fn candles(&self) -> &[Candle] {
+        &self.candles
+    }
 
-            // Draw price label on the right
-            let price_text = Text {
-                content: format!("{:.2}", price),
-                position: Point::new(chart_x + chart_width + 5.0, y),
-                color: text_color,
-                size: 12.0.into(),
-                horizontal_alignment: Horizontal::Left,
-                vertical_alignment: Vertical::Center,
-                ..Default::default()
-            };
-            frame.fill_text(price_text);
-        }
+    /// Bar positions currently shown, see [`Viewport`]
+    pub fn visible_range(&self) -> (f64, f64) {
+        let navigation = self.navigation.get();
+        let end = self.series().len() as f64 - navigation.offset;
+        (end - navigation.bars, end)
+    }
 
-        // Draw X-axis labels with adaptive formatting
-        let num_x_labels = 5.min(self.candles.len());
-        let step = if num_x_labels > 1 {
-            self.candles.len() / (num_x_labels - 1)
-        } else {
-            1
-        };
+    pub fn set_chart_type(&mut self, chart_type: ChartType) {
+        self.chart_type = chart_type;
+        self.derive_series();
+    }
 
-        // Determine date format based on zoom level and interval
-        // Total time span shown = visible_candles * interval_minutes
-        let visible_candles = self.candles.len();
-        let total_minutes = visible_candles as i64 * self.interval_minutes;
-
-        let date_format = if total_minutes <= 60 {
-            // Less than 1 hour: show time with seconds
-            "%H:%M:%S"
-        } else if total_minutes <= 360 {
-            // Less than 6 hours: show time HH:MM
-            "%H:%M"
-        } else if total_minutes <= 1440 {
-            // Less than 1 day: show time
-            "%m/%d %H:%M"
-        } else if total_minutes <= 10080 {
-            // Less than 1 week: show date and time
-            "%m/%d %H:%M"
-        } else if total_minutes <= 43200 {
-            // Less than 30 days: show month/day
-            "%m/%d"
-        } else {
-            // More than 30 days: show month/day
-            "%Y-%m-%d"
-        };
+    pub fn set_box_size(&mut self, box_size: BoxSize) {
+        self.box_size = box_size;
+        self.box_price = None;
+        self.derive_series();
+    }
 
-        for i in 0..num_x_labels {
-            let candle_idx = (i * step).min(self.candles.len() - 1);
-            let x = chart_x + (candle_idx as f32 / self.candles.len() as f32) * chart_width;
-            let y = chart_y + chart_height;
+    pub fn set_reversal(&mut self, boxes: usize) {
+        self.reversal = Some(boxes.max(1));
+        self.derive_series();
+    }
 
-            // Get the timestamp and format it adaptively
-            let timestamp = self.candles[candle_idx].timestamp;
-            let datetime = DateTime::from_timestamp(timestamp, 0)
-                .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
-            let date_string = datetime.format(date_format).to_string();
+    /// Reversal in boxes used by the current chart type
+    pub fn effective_reversal(&self) -> usize {
+        self.reversal.unwrap_or(self.chart_type.default_reversal())
+    }
 
-            // Draw X-axis label
-            let x_text = Text {
-                content: date_string,
-                position: Point::new(x, y + 15.0),
-                color: text_color,
-                size: 12.0.into(),
-                horizontal_alignment: Horizontal::Center,
-                vertical_alignment: Vertical::Center,
-                ..Default::default()
-            };
-            frame.fill_text(x_text);
+    /// Indicators drawn over the prices, with their colors
+    pub fn overlays(&self) -> impl Iterator<Item = (Overlay, Color)> + '_ {
+        self.overlays.iter().map(|overlay| (overlay.overlay, overlay.color))
+    }
+
+    /// Draw an indicator over the prices, or recolor it if already drawn
+    pub fn add_overlay(&mut self, overlay: Overlay, color: Color) {
+        match self.overlays.iter_mut().find(|lines| lines.overlay == overlay) {
+            Some(lines) => lines.color = color,
+            None => self.overlays.push(OverlayLines {
+                overlay,
+                color,
+                lines: overlay.compute(self.series()),
+                tracker: overlay.tracker(self.series()),
+            }),
         }
+        // Indicators have a layer of their own, the candles stay as drawn
+        self.overlay_cache.clear();
+    }
 
-        // Draw chart border
-        let border = Path::rectangle(
-            Point::new(chart_x, chart_y),
-            Size::new(chart_width, chart_height),
-        );
-        frame.stroke(
-            &border,
-            Stroke::default().with_width(1.0).with_color(Color::from_rgb(0.4, 0.4, 0.4)),
-        );
+    pub fn remove_overlay(&mut self, overlay: Overlay) {
+        self.overlays.retain(|lines| lines.overlay != overlay);
+        self.overlay_cache.clear();
+    }
 
-        // Calculate max volume for scaling
-        let max_volume = self.candles.iter()
-            .map(|c| c.volume)
-            .fold(f64::MIN, f64::max);
+    /// Panes below the prices, top to bottom
+    pub fn panes(&self) -> impl Iterator<Item = (PaneId, PaneKind)> + '_ {
+        self.panes.iter().map(|pane| (pane.id, pane.kind))
+    }
 
-        // Draw volume bars FIRST (so they appear behind candlesticks)
-        // Volume bars use the bottom 30% of the chart height
-        let volume_max_height = chart_height * 0.3;
+    /// Add a pane below the prices, under the existing ones
+    pub fn add_pane(&mut self, kind: PaneKind) -> PaneId {
+        let id = PaneId(self.next_pane_id);
+        self.next_pane_id += 1;
+        self.panes.push(Pane { id, kind });
+        self.pane_lines.insert(id, PaneLines {
+            lines: kind.compute(self.series()),
+            tracker: kind.tracker(self.series()),
+        });
+        self.clear_caches();
+        id
+    }
 
-        let num_candles = self.candles.len();
-        let candle_width = chart_width / num_candles as f32;
+    pub fn remove_pane(&mut self, id: PaneId) {
+        self.panes.retain(|pane| pane.id != id);
+        self.pane_lines.remove(&id);
+        self.clear_caches();
+    }
 
-        for (i, candle) in self.candles.iter().enumerate() {
-            let x = chart_x + i as f32 * candle_width;
-            let bar_width = candle_width * 0.8;
+    /// Swap a pane with the one above or below it
+    pub fn move_pane(&mut self, id: PaneId, up: bool) {
+        let Some(index) = self.panes.iter().position(|pane| pane.id == id) else {
+            return;
+        };
+        let other = if up { index.checked_sub(1) } else { Some(index + 1) };
+        if let Some(other) = other.filter(|other| *other < self.panes.len()) {
+            self.panes.swap(index, other);
+            self.clear_caches();
+        }
+    }
 
-            let volume_ratio = (candle.volume / max_volume) as f32;
-            let bar_height = volume_ratio * volume_max_height;
+    pub fn set_volume_pane(&mut self, enabled: bool) {
+        let has_volume = self.panes.iter().any(|pane| pane.kind == PaneKind::Volume);
+        if enabled && !has_volume {
+            self.add_pane(PaneKind::Volume);
+        } else if !enabled {
+            let volume: Vec<PaneId> = self.panes.iter().filter(|pane| pane.kind == PaneKind::Volume).map(|pane| pane.id).collect();
+            for id in volume {
+                self.remove_pane(id);
+            }
+        }
+    }
 
-            // Color volume bars based on candle direction with high transparency
-            let volume_color = if candle.is_bullish() {
-                Color::from_rgba(0.0, 0.8, 0.0, 0.2) // Very transparent green
-            } else {
-                Color::from_rgba(0.8, 0.0, 0.0, 0.2) // Very transparent red
-            };
+    pub fn set_volume_unit(&mut self, unit: VolumeUnit) {
+        self.volume_unit = unit;
+        self.average_volume();
+        self.clear_caches();
+    }
 
-            let volume_bar = Path::rectangle(
-                Point::new(x + candle_width * 0.1, chart_y + chart_height - bar_height),
-                Size::new(bar_width, bar_height),
-            );
-            frame.fill(&volume_bar, volume_color);
-        }
+    /// See [`CandlestickChart::future_margin`]
+    pub fn set_future_margin(&mut self, margin: Duration) {
+        let following = self.at_end();
+        self.future_margin = margin;
 
-        // Draw each candlestick (on top of volume bars)
-        let body_width = candle_width * 0.7;
-        let wick_width = candle_width * 0.1;
+        let navigation = self.navigation.get();
+        let min_offset = self.min_offset(navigation.bars);
+        self.set_navigation(Navigation {
+            // Keep following the newest candle, now with the new margin
+            offset: if following { min_offset } else { navigation.offset.max(min_offset) },
+            ..navigation
+        });
+    }
 
-        for (i, candle) in self.candles.iter().enumerate() {
-            let x = chart_x + i as f32 * candle_width + candle_width / 2.0;
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) {
+        self.scale_mode = mode;
+        self.clear_caches();
+    }
 
-            // Convert prices to screen coordinates (invert Y axis)
-            let open_y = chart_y + chart_height - ((candle.open - min_price) / price_span) as f32 * chart_height;
-            let close_y = chart_y + chart_height - ((candle.close - min_price) / price_span) as f32 * chart_height;
-            let high_y = chart_y + chart_height - ((candle.high - min_price) / price_span) as f32 * chart_height;
-            let low_y = chart_y + chart_height - ((candle.low - min_price) / price_span) as f32 * chart_height;
+    pub fn set_price_precision(&mut self, decimals: usize) {
+        self.price_precision = decimals;
+        self.clear_caches();
+    }
 
-            // Determine color based on bullish/bearish
-            let color = if candle.is_bullish() {
-                Color::from_rgb(0.0, 0.8, 0.0) // Green for bullish
-            } else {
-                Color::from_rgb(0.8, 0.0, 0.0) // Red for bearish
-            };
+    pub fn set_timezone(&mut self, timezone: DisplayTimezone) {
+        self.timezone = timezone;
+        self.clear_caches();
+    }
 
-            // Draw the wick (high to low line)
-            let wick = Path::line(Point::new(x, high_y), Point::new(x, low_y));
-            frame.stroke(
-                &wick,
-                Stroke::default().with_width(wick_width).with_color(color),
-            );
+    /// Show a loading indicator at the left edge while older candles are fetched
+    pub fn set_loading_history(&mut self, loading: bool) {
+        self.loading_history = loading;
+        self.clear_caches();
+    }
 
-            // Draw the body (open to close rectangle)
-            let body_top = open_y.min(close_y);
-            let body_height = (open_y - close_y).abs().max(1.0); // Ensure minimum height
+    /// Merge a live candle into the series: update the forming candle in place,
+    /// or append once a newer candle has opened
+    pub fn push_candle(&mut self, candle: Candle) {
+        let before = self.series().len();
+        match self.candles.last_mut() {
+            Some(last) if last.timestamp == candle.timestamp => *last = candle,
+            Some(last) if last.timestamp < candle.timestamp => self.candles.push(candle),
+            Some(_) => return,
+            None => self.candles.push(candle),
+        }
 
-            let body = Path::rectangle(
-                Point::new(x - body_width / 2.0, body_top),
-                Size::new(body_width, body_height),
-            );
+        self.last_candle_changed(before, self.candles.len() > before);
+    }
 
-            frame.fill(&body, color);
+    /// Update the derived series and indicators after the last candle changed
+    /// or, if `appended`, a new one was added, where the series had `before` bars
+    fn last_candle_changed(&mut self, before: usize, appended: bool) {
+        // Bars derived from the candles may all change
+        if self.chart_type.is_derived() {
+            self.derive_series();
+        } else {
+            self.track_last_candle(appended);
         }
 
-        // Draw crosshair and info box if cursor is present
-        if let Some(cursor_pos) = state.cursor_position {
-            // Only draw crosshair if cursor is within chart bounds
-            if cursor_pos.x >= chart_x && cursor_pos.x <= chart_x + chart_width
-                && cursor_pos.y >= chart_y && cursor_pos.y <= chart_y + chart_height
-            {
-                let crosshair_color = Color::from_rgba(0.8, 0.8, 0.8, 0.5);
-
-                // Draw vertical line
-                let vertical_line = Path::line(
-                    Point::new(cursor_pos.x, chart_y),
-                    Point::new(cursor_pos.x, chart_y + chart_height),
-                );
-                frame.stroke(
-                    &vertical_line,
-                    Stroke::default().with_width(1.0).with_color(crosshair_color),
-                );
+        // Keep the view still when panned back in history, derived series
+        // may gain any number of bars
+        let navigation = self.navigation.get();
+        if !self.at_end() {
+            self.navigation.set(Navigation {
+                offset: navigation.offset + self.series().len() as f64 - before as f64,
+                ..navigation
+            });
+        }
+    }
 
-                // Draw horizontal line
-                let horizontal_line = Path::line(
-                    Point::new(chart_x, cursor_pos.y),
-                    Point::new(chart_x + chart_width, cursor_pos.y),
-                );
-                frame.stroke(
-                    &horizontal_line,
-                    Stroke::default().with_width(1.0).with_color(crosshair_color),
-                );
+    /// Append a candle even if it opens in the same second as the last one,
+    /// as bars built from trades can
+    pub fn append_candle(&mut self, candle: Candle) {
+        let before = self.series().len();
+        self.candles.push(candle);
+        self.last_candle_changed(before, true);
+    }
 
-                // Calculate which candle is under cursor
-                let candle_index = ((cursor_pos.x - chart_x) / candle_width) as usize;
+    /// Replace the last candle, such as a forming bar built from trades
+    pub fn replace_last_candle(&mut self, candle: Candle) {
+        let before = self.series().len();
+        let appended = match self.candles.last_mut() {
+            Some(last) => {
+                *last = candle;
+                false
+            }
+            None => {
+                self.candles.push(candle);
+                true
+            }
+        };
+        self.last_candle_changed(before, appended);
+    }
 
-                if candle_index < self.candles.len() {
-                    let candle = &self.candles[candle_index];
+    /// Bring indicators up to date with the last candle without going through
+    /// the whole series again
+    fn track_last_candle(&mut self, appended: bool) {
+        let candles = &self.candles;
+        for overlay in &mut self.overlays {
+            let kind = overlay.overlay;
+            let values = overlay.tracker.next(candles, appended, |candles| kind.compute(candles));
+            set_last(&mut overlay.lines, values, appended);
+        }
+        for pane in &self.panes {
+            if let Some(pane_lines) = self.pane_lines.get_mut(&pane.id) {
+                let kind = pane.kind;
+                let values = pane_lines.tracker.next(candles, appended, |candles| kind.compute(candles));
+                set_last(&mut pane_lines.lines, values, appended);
+            }
+        }
+        let (unit, period) = (self.volume_unit, self.volume_ma);
+        let values = self
+            .volume_average
+            .tracker
+            .next(candles, appended, |candles| volume_average(candles, unit, period));
+        set_last(&mut self.volume_average.lines, values, appended);
+        self.clear_caches();
+    }
 
-                    // Format timestamp
-                    let datetime = DateTime::from_timestamp(candle.timestamp, 0)
-                        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
-                    let time_string = datetime.format("%Y-%m-%d %H:%M").to_string();
+    /// Add candles older than the first one, returning how many were added
+    ///
+    /// The view stays where it is.
+    pub fn prepend_history(&mut self, older: Vec<Candle>) -> usize {
+        let first = self.candles.first().map_or(i64::MAX, |c| c.timestamp);
+        let older: Vec<Candle> = older.into_iter().filter(|c| c.timestamp < first).collect();
+        let count = older.len();
 
-                    // Create info box at top right
-                    let info_box_x = chart_x + chart_width - 250.0;
-                    let info_box_y = chart_y + 10.0;
-                    let info_box_width = 240.0;
-                    let info_box_height = 110.0;
+        // The offset counts from the newest candle, so nothing moves
+        self.candles.splice(0..0, older);
+        self.box_price = None;
+        self.derive_series();
+        count
+    }
 
-                    // Draw semi-transparent background
-                    let info_bg = Path::rectangle(
-                        Point::new(info_box_x, info_box_y),
-                        Size::new(info_box_width, info_box_height),
-                    );
-                    frame.fill(&info_bg, Color::from_rgba(0.0, 0.0, 0.0, 0.8));
+    /// Candles as drawn: the series itself, or the one derived from it for
+    /// the chart type
+    fn series(&self) -> &[Candle] {
+        if self.chart_type.is_derived() { &self.derived } else { &self.candles }
+    }
 
-                    // Draw border
-                    frame.stroke(
-                        &info_bg,
-                        Stroke::default().with_width(1.0).with_color(Color::from_rgb(0.5, 0.5, 0.5)),
-                    );
+    /// Recompute the derived series and indicators after the candles or chart
+    /// settings changed
+    fn derive_series(&mut self) {
+        // An ATR box follows the series as loaded, live candles don't re-brick it
+        if self.box_price.is_none() {
+            self.box_price = self.box_size.resolve(&self.candles);
+        }
+        let box_size = self.box_price;
+        let reversal = self.effective_reversal();
 
-                    let text_color = Color::from_rgb(0.9, 0.9, 0.9);
-                    let text_size = 12.0;
-                    let line_height = 16.0;
-
-                    // Draw time
-                    let time_text = Text {
-                        content: format!("Time: {}", time_string),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(time_text);
-
-                    // Draw OHLC
-                    let open_text = Text {
-                        content: format!("O: {:.2}", candle.open),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(open_text);
-
-                    let high_text = Text {
-                        content: format!("H: {:.2}", candle.high),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height * 2.0),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(high_text);
-
-                    let low_text = Text {
-                        content: format!("L: {:.2}", candle.low),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height * 3.0),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(low_text);
-
-                    let close_text = Text {
-                        content: format!("C: {:.2}", candle.close),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height * 4.0),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(close_text);
-
-                    // Draw volume
-                    let volume_text = Text {
-                        content: format!("Vol: {:.0}", candle.volume),
-                        position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height * 5.0),
-                        color: text_color,
-                        size: text_size.into(),
-                        horizontal_alignment: Horizontal::Left,
-                        vertical_alignment: Vertical::Top,
-                        ..Default::default()
-                    };
-                    frame.fill_text(volume_text);
-                }
+        let bars = match (self.chart_type, box_size) {
+            (ChartType::HeikinAshi, _) => {
+                self.derived = transform::heikin_ashi(&self.candles);
+                self.derived_ends.clear();
+                Vec::new()
             }
+            (ChartType::Renko, Some(size)) => transform::renko(&self.candles, size, reversal),
+            (ChartType::Kagi, Some(size)) => transform::kagi(&self.candles, size * reversal as f64),
+            (ChartType::PointAndFigure, Some(size)) => transform::point_and_figure(&self.candles, size, reversal),
+            _ => Vec::new(),
+        };
+
+        if self.chart_type != ChartType::HeikinAshi {
+            (self.derived, self.derived_ends) = bars.into_iter().map(|bar| (bar.candle, bar.end)).unzip();
         }
 
-        vec![frame.into_geometry()]
+        // Indicators follow the bars as drawn
+        let series = if self.chart_type.is_derived() { &self.derived } else { &self.candles };
+        for overlay in &mut self.overlays {
+            overlay.lines = overlay.overlay.compute(series);
+            overlay.tracker = overlay.overlay.tracker(series);
+        }
+        self.pane_lines = self
+            .panes
+            .iter()
+            .map(|pane| {
+                let lines = PaneLines {
+                    lines: pane.kind.compute(series),
+                    tracker: pane.kind.tracker(series),
+                };
+                (pane.id, lines)
+            })
+            .collect();
+        self.average_volume();
+        self.clear_caches();
+    }
+
+    /// Recompute the volume moving average over the series
+    fn average_volume(&mut self) {
+        self.volume_average = PaneLines {
+            lines: volume_average(self.series(), self.volume_unit, self.volume_ma),
+            tracker: Tracker::Window(self.volume_ma.unwrap_or(0)),
+        };
+    }
+
+    /// Whether kinetic scrolling is running, calling for [`CandlestickChart::tick`]
+    /// on every frame
+    pub fn is_animating(&self) -> bool {
+        self.navigation.get().velocity != 0.0
+    }
+
+    /// Advance kinetic scrolling to `now`
+    pub fn tick(&self, now: Instant) -> Option<ChartMessage> {
+        let mut navigation = self.navigation.get();
+        if navigation.velocity == 0.0 {
+            return None;
+        }
+
+        let elapsed = navigation.last_frame.map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+        navigation.last_frame = Some(now);
+
+        let velocity = navigation.velocity;
+        navigation.velocity *= (1.0 - FLING_FRICTION).powf(elapsed);
+        if navigation.velocity.abs() < MIN_FLING_SPEED {
+            navigation.velocity = 0.0;
+        }
+        self.navigation.set(navigation);
+
+        let message = self.scroll(velocity * elapsed);
+        if matches!(message, ChartMessage::NeedMoreHistory) || self.at_end() {
+            // Slid into either end of the series
+            self.fling(0.0);
+        }
+        Some(message)
+    }
+
+    /// Scroll by `bars`, positive towards older candles, asking for more
+    /// history when stopped at the oldest candle
+    fn scroll(&self, bars: f64) -> ChartMessage {
+        let navigation = self.navigation.get();
+        let max_offset = (self.series().len() as f64 - navigation.bars).max(0.0);
+        let offset = navigation.offset + bars;
+
+        self.set_navigation(Navigation {
+            offset: offset.clamp(self.min_offset(navigation.bars), max_offset),
+            ..navigation
+        });
+
+        if bars > 0.0 && offset >= max_offset {
+            ChartMessage::NeedMoreHistory
+        } else {
+            self.viewport_changed()
+        }
+    }
+
+    /// Scale the number of bars shown by `factor` (above 1 zooms out), keeping
+    /// the bar at `anchor` in place, from 0.0 at the left edge to 1.0 at the right
+    fn zoom(&self, factor: f64, anchor: f64) -> ChartMessage {
+        let navigation = self.navigation.get();
+        let len = self.series().len() as f64;
+        let end = len - navigation.offset;
+        let anchor_bar = end - navigation.bars * (1.0 - anchor);
+
+        let bars = (navigation.bars * factor).clamp(MIN_VISIBLE_BARS, (len + self.future_bars()).max(MIN_VISIBLE_BARS));
+        let end = anchor_bar + bars * (1.0 - anchor);
+
+        self.set_navigation(Navigation {
+            bars,
+            offset: (len - end).clamp(self.min_offset(bars), (len - bars).max(0.0)),
+            ..navigation
+        });
+        self.viewport_changed()
+    }
+
+    /// Start kinetic scrolling at `velocity` bars per second, or stop it with 0.0
+    fn fling(&self, velocity: f64) {
+        let velocity = if velocity.abs() < MIN_FLING_SPEED { 0.0 } else { velocity };
+        self.navigation.set(Navigation {
+            velocity,
+            last_frame: None,
+            ..self.navigation.get()
+        });
+    }
+
+    /// Showing the newest candle, and the future margin after it, at the
+    /// right edge
+    fn at_end(&self) -> bool {
+        let navigation = self.navigation.get();
+        navigation.offset <= self.min_offset(navigation.bars)
+    }
+
+    /// Offset showing the whole future margin, or as much of it as leaves a
+    /// candle in view when `bars` are shown
+    fn min_offset(&self, bars: f64) -> f64 {
+        -self.future_bars().min(bars - 1.0).max(0.0)
+    }
+
+    /// Bars in the future margin
+    fn future_bars(&self) -> f64 {
+        self.future_margin.as_secs_f64() / self.bar_seconds()
+    }
+
+    fn set_navigation(&self, navigation: Navigation) {
+        self.navigation.set(navigation);
+        self.clear_caches();
+    }
+
+    fn viewport_changed(&self) -> ChartMessage {
+        let (start, end) = self.visible_range();
+        ChartMessage::ViewportChanged { start, end }
+    }
+
+    /// Timestamp at a bar position, extrapolated past the last candle
+    fn time_at(&self, index: f64) -> i64 {
+        let last = self.series().len() - 1;
+        let i = (index.floor().max(0.0) as usize).min(last);
+        self.series()[i].timestamp + ((index - i as f64) * self.bar_seconds()) as i64
+    }
+
+    /// Time a bar stands for: the interval, or the average spacing of bars
+    /// that are not evenly spaced in time
+    pub fn bar_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.bar_seconds())
+    }
+
+    /// [`CandlestickChart::bar_duration`] in seconds
+    fn bar_seconds(&self) -> f64 {
+        if self.interval_minutes > 0 && self.chart_type.is_time_based() {
+            return (self.interval_minutes * 60) as f64;
+        }
+
+        match (self.series().first(), self.series().last()) {
+            (Some(first), Some(last)) if self.series().len() > 1 => {
+                ((last.timestamp - first.timestamp) as f64 / (self.series().len() - 1) as f64).max(1.0)
+            }
+            _ => 60.0,
+        }
+    }
+
+    /// Bar position of a timestamp, interpolated between candles so that
+    /// irregular spacing is handled
+    fn index_of_time(&self, timestamp: i64) -> Option<f64> {
+        let after = self.series().partition_point(|c| c.timestamp < timestamp);
+
+        if after == self.series().len() {
+            // Extrapolated past the last candle, into the future margin
+            let last = self.series().last()?;
+            let offset = (timestamp - last.timestamp) as f64 / self.bar_seconds();
+            return Some((after - 1) as f64 + offset);
+        }
+
+        let next = self.series()[after].timestamp;
+        if next == timestamp {
+            return Some(after as f64);
+        }
+
+        let previous = self.series().get(after.checked_sub(1)?)?.timestamp;
+        Some((after - 1) as f64 + (timestamp - previous) as f64 / (next - previous) as f64)
+    }
+
+    pub fn view(&self) -> Element<'_, ChartMessage> {
+        Canvas::new(self)
+            .width(self.width)
+            .height(self.height)
+            .into()
+    }
+}
+
+/// Plotting area of a chart drawn in `size`, without the axes
+fn chart_area(size: Size) -> Rectangle {
+    Rectangle::new(
+        Point::new(LEFT_MARGIN, TOP_MARGIN),
+        Size::new(
+            (size.width - LEFT_MARGIN - RIGHT_MARGIN).max(1.0),
+            (size.height - TOP_MARGIN - BOTTOM_MARGIN).max(1.0),
+        ),
+    )
+}
+
+pub struct ChartState {
+    focused: bool, // Clicked last, so keyboard input goes to this chart
+    dragging: bool,
+    press_position: Point, // Where the current drag started
+    last_x: f32,
+    cursor_position: Option<Point>,
+    scaling_price: bool, // Dragging on the price axis
+    last_y: f32,
+    price_scale: f64, // Vertical stretch on top of the auto-fit, 1.0 = auto-fit
+    resizing: Option<usize>,            // Index of the pane whose top divider is dragged
+    pane_ratios: HashMap<PaneId, f32>, // Share of the height of resized panes
+    last_click: Option<Instant>,
+    last_move: Option<Instant>, // Time of the last drag step
+    velocity: f64,              // Drag speed in bars per second, smoothed
+}
+
+impl Default for ChartState {
+    fn default() -> Self {
+        Self {
+            focused: false,
+            dragging: false,
+            press_position: Point::ORIGIN,
+            last_x: 0.0,
+            cursor_position: None,
+            scaling_price: false,
+            last_y: 0.0,
+            price_scale: 1.0,
+            resizing: None,
+            pane_ratios: HashMap::new(),
+            last_click: None,
+            last_move: None,
+            velocity: 0.0,
+        }
+    }
+}
+
+impl CandlestickChart {
+    /// Split the plotting area of a chart drawn in `size` into panes
+    fn layout(&self, size: Size, state: &ChartState) -> Layout {
+        Layout::new(chart_area(size), &self.panes, |id| {
+            state.pane_ratios.get(&id).copied().unwrap_or(DEFAULT_PANE_RATIO)
+        })
+    }
+}
+
+impl canvas::Program<ChartMessage> for CandlestickChart {
+    type State = ChartState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (Status, Option<ChartMessage>) {
+        let layout = self.layout(bounds.size(), state);
+        let area = layout.price;
+
+        if self.series().is_empty() {
+            return (Status::Ignored, None);
+        }
+
+        match event {
+            Event::Mouse(mouse_event) => match mouse_event {
+                iced::mouse::Event::WheelScrolled { delta } => {
+                    // Scrolling up zooms in
+                    let amount = match delta {
+                        ScrollDelta::Lines { y, .. } => y as f64 * ZOOM_PER_LINE,
+                        ScrollDelta::Pixels { y, .. } => y as f64 * ZOOM_PER_PIXEL,
+                    };
+
+                    // Zoom around the cursor, or the latest bar when it is off the chart
+                    let anchor = cursor
+                        .position_in(bounds)
+                        .map_or(1.0, |position| ((position.x - area.x) / area.width).clamp(0.0, 1.0) as f64);
+
+                    (Status::Captured, Some(self.zoom((-amount).exp(), anchor)))
+                }
+                iced::mouse::Event::ButtonPressed(_) if !cursor.is_over(bounds) => {
+                    state.focused = false;
+                    (Status::Ignored, None)
+                }
+                iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left) => {
+                    state.focused = true;
+                    if let Some(position) = cursor.position_in(bounds) {
+                        let now = Instant::now();
+                        let double_click = state
+                            .last_click
+                            .is_some_and(|last| now.duration_since(last) < DOUBLE_CLICK);
+                        state.last_click = Some(now);
+
+                        let on_price_axis = position.x > area.x + area.width
+                            && position.y >= area.y
+                            && position.y <= area.y + area.height;
+
+                        if let Some((pane, button)) = layout.button_at(position) {
+                            let message = match button {
+                                PaneButton::Up => ChartMessage::PaneMoveRequested { pane, up: true },
+                                PaneButton::Down => ChartMessage::PaneMoveRequested { pane, up: false },
+                                PaneButton::Close => ChartMessage::PaneCloseRequested(pane),
+                            };
+                            return (Status::Captured, Some(message));
+                        }
+
+                        if let Some(divider) = layout.divider_at(position) {
+                            state.resizing = Some(divider);
+                        } else if on_price_axis {
+                            if double_click {
+                                // Back to auto-fit
+                                state.price_scale = 1.0;
+                                self.clear_caches();
+                            } else {
+                                state.scaling_price = true;
+                                state.last_y = position.y;
+                            }
+                        } else {
+                            state.dragging = true;
+                            state.press_position = position;
+                            state.last_x = position.x;
+                            state.last_move = Some(now);
+                            state.velocity = 0.0;
+
+                            // Grabbing the chart stops it sliding
+                            self.fling(0.0);
+                        }
+                        (Status::Captured, None)
+                    } else {
+                        (Status::Ignored, None)
+                    }
+                }
+                iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left) => {
+                    let was_dragging = state.dragging;
+                    state.dragging = false;
+                    state.scaling_price = false;
+                    state.resizing = None;
+
+                    if !was_dragging {
+                        return (Status::Captured, None);
+                    }
+
+                    // Barely moved: a click on the candle under the cursor
+                    if let Some(position) = cursor.position_in(bounds)
+                        && position.distance(state.press_position) < CLICK_SLOP
+                    {
+                        let viewport = self.viewport(state);
+                        let index = viewport.map(|viewport| viewport.index_at(position.x, area).floor());
+                        let candle = index
+                            .filter(|index| *index >= 0.0)
+                            .and_then(|index| self.series().get(index as usize));
+                        return (Status::Captured, candle.copied().map(ChartMessage::CandleClicked));
+                    }
+
+                    // Keep sliding only if the mouse was still moving when released
+                    let moving = state
+                        .last_move
+                        .is_some_and(|last| last.elapsed() < FLING_TIMEOUT);
+                    self.fling(if moving { state.velocity } else { 0.0 });
+                    // A message makes the host subscribe to the frames that
+                    // drive the slide
+                    let message = self.is_animating().then(|| self.viewport_changed());
+                    (Status::Captured, message)
+                }
+                iced::mouse::Event::CursorMoved { .. } => {
+                    state.cursor_position = cursor.position_in(bounds);
+
+                    if let Some(divider) = state.resizing {
+                        if let Some(position) = cursor.position_in(bounds) {
+                            state.pane_ratios.extend(layout.resize(divider, position.y));
+                            self.clear_caches();
+                        }
+                        (Status::Captured, None)
+                    } else if state.scaling_price {
+                        if let Some(position) = cursor.position_in(bounds) {
+                            // Dragging down compresses the candles, up stretches them
+                            let delta = position.y - state.last_y;
+                            state.last_y = position.y;
+                            state.price_scale = (state.price_scale * (delta as f64 * 0.005).exp()).clamp(0.01, 100.0);
+                            self.clear_caches();
+                        }
+                        (Status::Captured, None)
+                    } else if state.dragging {
+                        if let Some(position) = cursor.position_in(bounds) {
+                            // Move the content exactly with the mouse, dragging
+                            // right shows older candles
+                            let bar_width = area.width as f64 / self.navigation.get().bars;
+                            let delta = (position.x - state.last_x) as f64 / bar_width;
+                            state.last_x = position.x;
+
+                            let now = Instant::now();
+                            if let Some(last) = state.last_move {
+                                let elapsed = now.duration_since(last).as_secs_f64();
+                                if elapsed > 0.0 {
+                                    state.velocity = 0.8 * delta / elapsed + 0.2 * state.velocity;
+                                }
+                            }
+                            state.last_move = Some(now);
+
+                            (Status::Captured, Some(self.scroll(delta)))
+                        } else {
+                            (Status::Ignored, None)
+                        }
+                    } else {
+                        (Status::Ignored, None)
+                    }
+                }
+                iced::mouse::Event::CursorLeft => {
+                    state.cursor_position = None;
+                    (Status::Ignored, None)
+                }
+                _ => (Status::Ignored, None),
+            },
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modified_key, modifiers, .. }) if state.focused => {
+                // `modified_key` tells "+" from "=" on layouts where it needs Shift
+                let key = match modified_key {
+                    Key::Character(_) => modified_key,
+                    _ => key,
+                };
+                match self.key_pressed(state, key, modifiers, area) {
+                    Some(message) => (Status::Captured, Some(message)),
+                    None => (Status::Ignored, None),
+                }
+            }
+            _ => (Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let Some(viewport) = self.viewport(state) else {
+            return Vec::new();
+        };
+
+        let layout = self.layout(bounds.size(), state);
+
+        // Indicators take part in fitting the prices, so the candles and axes
+        // move when they change
+        if self.drawn_viewport.replace(Some(viewport)) != Some(viewport) {
+            self.clear_caches();
+        }
+
+        // Only the crosshair changes on hover, everything else is redrawn
+        // from the caches until data, viewport or style change
+        let background = self.background_cache.draw(renderer, bounds.size(), |frame| {
+            self.draw_background(frame, &viewport, &layout);
+        });
+        let series = self.series_cache.draw(renderer, bounds.size(), |frame| {
+            self.draw_series(frame, &viewport, &layout);
+        });
+        let overlays = self.overlay_cache.draw(renderer, bounds.size(), |frame| {
+            self.draw_overlays(frame, &viewport, layout.price);
+        });
+
+        let mut crosshair = Frame::new(renderer, bounds.size());
+        if let Some(cursor_pos) = state.cursor_position {
+            self.draw_crosshair(&mut crosshair, &viewport, &layout, cursor_pos);
+        }
+
+        vec![background, series, overlays, crosshair.into_geometry()]
+    }
+
+    fn mouse_interaction(&self, state: &ChartState, bounds: Rectangle, cursor: Cursor) -> iced::mouse::Interaction {
+        let layout = self.layout(bounds.size(), state);
+        let position = cursor.position_in(bounds);
+
+        if state.resizing.is_some() || position.is_some_and(|position| layout.divider_at(position).is_some()) {
+            iced::mouse::Interaction::ResizingVertically
+        } else if position.is_some_and(|position| layout.button_at(position).is_some()) {
+            iced::mouse::Interaction::Pointer
+        } else {
+            iced::mouse::Interaction::default()
+        }
+    }
+}
+
+impl CandlestickChart {
+    /// Keyboard navigation: arrows pan by a bar (a page with Shift), +/- zoom,
+    /// Home/End jump to the oldest/latest candle and up/down step the
+    /// crosshair between candles
+    fn key_pressed(&self, state: &mut ChartState, key: Key, modifiers: Modifiers, area: Rectangle) -> Option<ChartMessage> {
+        let step = if modifiers.shift() { self.navigation.get().bars } else { 1.0 };
+        let len = self.series().len() as f64;
+
+        match key.as_ref() {
+            Key::Named(Named::ArrowLeft) => Some(self.scroll(step)),
+            Key::Named(Named::ArrowRight) => Some(self.scroll(-step)),
+            Key::Named(Named::Home) => Some(self.scroll(len)),
+            Key::Named(Named::End) => Some(self.scroll(-len)),
+            Key::Character("+" | "=") => Some(self.zoom((-ZOOM_PER_LINE).exp(), 1.0)),
+            Key::Character("-") => Some(self.zoom(ZOOM_PER_LINE.exp(), 1.0)),
+            Key::Named(Named::ArrowUp) => Some(self.step_crosshair(state, 1, area)),
+            Key::Named(Named::ArrowDown) => Some(self.step_crosshair(state, -1, area)),
+            _ => None,
+        }
+    }
+
+    /// Move the crosshair `step` candles forward in time onto their close,
+    /// scrolling to keep it in view; starts from the latest visible candle
+    fn step_crosshair(&self, state: &mut ChartState, step: isize, area: Rectangle) -> ChartMessage {
+        let last = self.series().len() - 1;
+        let (start, end) = self.visible_range();
+        let current = state
+            .cursor_position
+            .filter(|position| area.contains(*position))
+            .and_then(|position| self.viewport(state).map(|viewport| viewport.index_at(position.x, area).floor()))
+            .unwrap_or(end - 1.0)
+            .clamp(0.0, last as f64) as usize;
+        let index = current.saturating_add_signed(step).min(last);
+
+        // Scroll just enough to bring the candle into view
+        let target = index as f64;
+        let message = if target < start.ceil() {
+            self.scroll(start.ceil() - target)
+        } else if target + 1.0 > end.floor() {
+            self.scroll(end.floor() - target - 1.0)
+        } else {
+            self.viewport_changed()
+        };
+
+        if let Some(viewport) = self.viewport(state) {
+            state.cursor_position = Some(Point::new(
+                viewport.x(target + 0.5, area),
+                viewport.y(self.series()[index].close, area),
+            ));
+        }
+        message
+    }
+
+    /// Viewport over the visible range, with prices fitted to it and the user's scaling on top
+    fn viewport(&self, state: &ChartState) -> Option<Viewport> {
+        let (start, end) = self.visible_range();
+        Viewport::fit(self.series(), self.overlay_values(start, end), start, end, state.price_scale, self.scale_mode)
+    }
+
+    /// Values of the indicators drawn over bars `start..end`, including those
+    /// projected past the last candle
+    fn overlay_values(&self, start: f64, end: f64) -> impl Iterator<Item = f64> + '_ {
+        let bars = start.floor().max(0.0) as usize..end.ceil().max(0.0) as usize;
+        self.overlays.iter().flat_map(move |overlay| {
+            let bars = bars.clone();
+            (0..overlay.lines.len()).flat_map(move |line| bars.clone().filter_map(move |i| overlay_value(overlay, line, i)))
+        })
+    }
+
+    /// Drop every cached layer, to be redrawn on the next frame
+    fn clear_caches(&self) {
+        self.background_cache.clear();
+        self.series_cache.clear();
+        self.overlay_cache.clear();
+    }
+
+    /// Background, grid lines, axis labels and border
+    fn draw_background(&self, frame: &mut Frame, viewport: &Viewport, layout: &Layout) {
+        let area = layout.price;
+        let chart_width = area.width;
+        let chart_height = area.height;
+        let chart_x = area.x;
+
+        // Draw black background
+        let background = Path::rectangle(Point::ORIGIN, frame.size());
+        frame.fill(&background, self.style.background);
+
+        // Draw grid lines and Y-axis labels (prices), roughly one every 50px
+        let max_price_lines = (chart_height / 50.0).max(2.0) as usize;
+        let grid_color = self.style.grid;
+        let text_color = self.style.text;
+
+        let ticks = viewport.price_ticks(max_price_lines);
+        // Ticks closer together than the price precision get more decimals,
+        // or neighbouring labels would read the same
+        let precision = ticks
+            .windows(2)
+            .map(|pair| axis::decimals_for(pair[1] - pair[0]))
+            .fold(self.price_precision, usize::max);
+
+        for price in ticks {
+            let y = viewport.y(price, area);
+
+            // Draw grid line
+            let grid_line = Path::line(
+                Point::new(chart_x, y),
+                Point::new(chart_x + chart_width, y),
+            );
+            frame.stroke(
+                &grid_line,
+                Stroke::default().with_width(1.0).with_color(grid_color),
+            );
+
+            // Draw price label on the right
+            let price_text = Text {
+                content: viewport.label(price, precision),
+                position: Point::new(chart_x + chart_width + 5.0, y),
+                color: text_color,
+                size: 12.0.into(),
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Center,
+                ..Default::default()
+            };
+            frame.fill_text(price_text);
+        }
+
+        // Draw time ticks on calendar boundaries, with vertical grid lines
+        let first_time = self.time_at(viewport.start.max(0.0));
+        let last_time = self.time_at(viewport.end);
+        let max_time_labels = (chart_width / 90.0).max(2.0) as usize;
+
+        // Bars unevenly spaced in time can bunch ticks up, keep labels apart
+        let mut last_label_x = f32::NEG_INFINITY;
+
+        for tick in axis::time_ticks(first_time, last_time, max_time_labels, self.timezone) {
+            let Some(index) = self.index_of_time(tick.timestamp) else {
+                continue;
+            };
+            let x = viewport.x(index, area);
+            if x < chart_x || x > chart_x + chart_width || x - last_label_x < 60.0 {
+                continue;
+            }
+            last_label_x = x;
+
+            for pane in layout.all() {
+                let grid_line = Path::line(
+                    Point::new(x, pane.y),
+                    Point::new(x, pane.y + pane.height),
+                );
+                frame.stroke(
+                    &grid_line,
+                    Stroke::default().with_width(1.0).with_color(grid_color),
+                );
+            }
+
+            // Draw X-axis label, bold where a higher-level boundary is crossed
+            let x_text = Text {
+                content: tick.label,
+                position: Point::new(x, layout.bottom() + 15.0),
+                color: text_color,
+                size: 12.0.into(),
+                font: if tick.major { BOLD } else { Font::DEFAULT },
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..Default::default()
+            };
+            frame.fill_text(x_text);
+        }
+
+        // Each pane below the prices has its own axis, title and buttons
+        for (pane, pane_area) in &layout.panes {
+            let range = self.pane_range(*pane, viewport);
+            let max_lines = (pane_area.height / 40.0).max(1.0) as usize;
+            let ticks = match pane.kind.thresholds() {
+                Some((low, high)) => vec![low, 50.0, high],
+                None => axis::nice_ticks(range.0, range.1, max_lines),
+            };
+
+            for value in ticks {
+                let y = value_y(value, range, *pane_area);
+
+                let grid_line = Path::line(
+                    Point::new(pane_area.x, y),
+                    Point::new(pane_area.x + pane_area.width, y),
+                );
+                frame.stroke(
+                    &grid_line,
+                    Stroke::default().with_width(1.0).with_color(grid_color),
+                );
+
+                let value_text = Text {
+                    content: self.pane_label(pane.kind, value),
+                    position: Point::new(pane_area.x + pane_area.width + 5.0, y),
+                    color: text_color,
+                    size: 12.0.into(),
+                    horizontal_alignment: Horizontal::Left,
+                    vertical_alignment: Vertical::Center,
+                    ..Default::default()
+                };
+                frame.fill_text(value_text);
+            }
+
+            let title = Text {
+                content: self.pane_title(pane.kind),
+                position: Point::new(pane_area.x + 6.0, pane_area.y + 4.0),
+                color: text_color,
+                size: 12.0.into(),
+                horizontal_alignment: Horizontal::Left,
+                vertical_alignment: Vertical::Top,
+                ..Default::default()
+            };
+            frame.fill_text(title);
+
+            self.draw_pane_buttons(frame, *pane_area);
+        }
+
+        // Draw a border around each pane
+        for pane in layout.all() {
+            let border = Path::rectangle(pane.position(), pane.size());
+            frame.stroke(
+                &border,
+                Stroke::default().with_width(1.0).with_color(self.style.border),
+            );
+        }
+    }
+
+    /// Move up, move down and close buttons in the corner of a pane
+    fn draw_pane_buttons(&self, frame: &mut Frame, area: Rectangle) {
+        let stroke = Stroke::default().with_width(1.0).with_color(self.style.text);
+
+        for button in PaneButton::ALL {
+            let bounds = button.bounds(area);
+            frame.fill(&Path::rectangle(bounds.position(), bounds.size()), self.style.background);
+            frame.stroke(
+                &Path::rectangle(bounds.position(), bounds.size()),
+                Stroke::default().with_width(1.0).with_color(self.style.border),
+            );
+
+            // Glyphs drawn as paths, inset from the button edges
+            let (left, right) = (bounds.x + 4.0, bounds.x + bounds.width - 4.0);
+            let (top, bottom) = (bounds.y + 4.0, bounds.y + bounds.height - 4.0);
+            let middle = bounds.center_x();
+            let glyph = Path::new(|builder| match button {
+                PaneButton::Up => {
+                    builder.move_to(Point::new(left, bottom));
+                    builder.line_to(Point::new(middle, top));
+                    builder.line_to(Point::new(right, bottom));
+                }
+                PaneButton::Down => {
+                    builder.move_to(Point::new(left, top));
+                    builder.line_to(Point::new(middle, bottom));
+                    builder.line_to(Point::new(right, top));
+                }
+                PaneButton::Close => {
+                    builder.move_to(Point::new(left, top));
+                    builder.line_to(Point::new(right, bottom));
+                    builder.move_to(Point::new(right, top));
+                    builder.line_to(Point::new(left, bottom));
+                }
+            });
+            frame.stroke(&glyph, stroke);
+        }
+    }
+
+    /// Values a pane spans, bottom to top
+    fn pane_range(&self, pane: Pane, viewport: &Viewport) -> (f64, f64) {
+        if pane.kind == PaneKind::Volume {
+            return (0.0, self.max_volume(viewport));
+        }
+        if pane.kind.thresholds().is_some() {
+            return (0.0, 100.0);
+        }
+
+        // Fit the visible values, and zero for the MACD histogram
+        let visible = viewport.visible(self.series().len());
+        let lines = self.pane_lines.get(&pane.id).map_or(&[][..], |pane| pane.lines.as_slice());
+        let (mut min, mut max) = lines
+            .iter()
+            .flat_map(|line| line.get(visible.clone()).unwrap_or_default().iter().flatten())
+            .fold((f64::MAX, f64::MIN), |(min, max), value| (min.min(*value), max.max(*value)));
+        if let PaneKind::Macd { .. } = pane.kind {
+            (min, max) = (min.min(0.0), max.max(0.0));
+        }
+
+        if min > max {
+            (0.0, 1.0)
+        } else if min == max {
+            (min - 1.0, max + 1.0)
+        } else {
+            let padding = (max - min) * 0.1;
+            (min - padding, max + padding)
+        }
+    }
+
+    /// Axis label of `value` in a pane
+    fn pane_label(&self, kind: PaneKind, value: f64) -> String {
+        match kind {
+            PaneKind::Volume => axis::abbreviate(value),
+            PaneKind::Rsi(_) | PaneKind::Stochastic { .. } => format!("{:.0}", value),
+            PaneKind::Macd { .. } | PaneKind::Atr(_) => format!("{:.*}", self.price_precision, value),
+        }
+    }
+
+    /// Name shown in the top left corner of a pane
+    fn pane_title(&self, kind: PaneKind) -> String {
+        match (kind, self.volume_ma) {
+            (PaneKind::Volume, Some(period)) => format!("{}  MA {}", self.volume_unit, period),
+            (PaneKind::Volume, None) => self.volume_unit.to_string(),
+            _ => kind.to_string(),
+        }
+    }
+
+    /// Contents of a pane below the prices
+    fn draw_pane(&self, frame: &mut Frame, viewport: &Viewport, pane: Pane, area: Rectangle) {
+        let range = self.pane_range(pane, viewport);
+        let lines = self.pane_lines.get(&pane.id).map_or(&[][..], |pane| pane.lines.as_slice());
+
+        frame.with_clip(area, |frame| {
+            frame.translate(Vector::new(-area.x, -area.y));
+
+            // Shade between the oversold and overbought levels
+            if let Some((low, high)) = pane.kind.thresholds() {
+                let (top, bottom) = (value_y(high, range, area), value_y(low, range, area));
+                frame.fill(
+                    &Path::rectangle(Point::new(area.x, top), Size::new(area.width, bottom - top)),
+                    Color { a: 0.08, ..self.style.line },
+                );
+                for y in [top, bottom] {
+                    frame.stroke(
+                        &Path::line(Point::new(area.x, y), Point::new(area.x + area.width, y)),
+                        Stroke {
+                            line_dash: LineDash { segments: &[4.0, 4.0], offset: 0 },
+                            ..Stroke::default().with_width(1.0).with_color(self.style.border)
+                        },
+                    );
+                }
+            }
+
+            match (pane.kind, lines) {
+                (PaneKind::Volume, _) => self.draw_volume(frame, viewport, area, range),
+                (PaneKind::Macd { .. }, [line, signal, histogram]) => {
+                    self.draw_histogram(frame, viewport, area, range, histogram);
+                    self.stroke_values(frame, viewport, area, range, line, self.style.line);
+                    self.stroke_values(frame, viewport, area, range, signal, self.style.signal);
+                }
+                (_, lines) => {
+                    for (line, color) in lines.iter().zip([self.style.line, self.style.signal]) {
+                        self.stroke_values(frame, viewport, area, range, line, color);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Bars from zero to each value, in the bullish color above zero and the
+    /// bearish one below
+    fn draw_histogram(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle, range: (f64, f64), values: &[Option<f64>]) {
+        let candle_width = viewport.bar_width(area);
+        let zero = value_y(0.0, range, area);
+
+        for i in viewport.visible(self.series().len()) {
+            let Some(value) = values.get(i).copied().flatten() else {
+                continue;
+            };
+            let y = value_y(value, range, area);
+            let color = if value >= 0.0 { self.style.bullish } else { self.style.bearish };
+            let bar = Path::rectangle(
+                Point::new(viewport.x(i as f64, area) + candle_width * 0.1, y.min(zero)),
+                Size::new(candle_width * 0.8, (y - zero).abs()),
+            );
+            frame.fill(&bar, Color { a: 0.6, ..color });
+        }
+    }
+
+    /// Line through the values of an indicator in a pane, broken where it has none
+    fn stroke_values(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle, range: (f64, f64), values: &[Option<f64>], color: Color) {
+        let visible = viewport.visible(self.series().len());
+        // One bar either side so the line reaches the edges
+        let points = (visible.start.saturating_sub(1)..(visible.end + 1).min(values.len())).map(|i| {
+            values[i].map(|value| Point::new(viewport.x(i as f64 + 0.5, area), value_y(value, range, area)))
+        });
+        frame.stroke(&line_path(points), Stroke::default().with_width(1.5).with_color(color));
+    }
+
+    /// Top of the volume scale: the largest visible volume with some headroom
+    fn max_volume(&self, viewport: &Viewport) -> f64 {
+        let visible = viewport.visible(self.series().len());
+        let max = self.series()[visible]
+            .iter()
+            .map(|c| self.volume_unit.of(c))
+            .fold(0.0, f64::max);
+
+        if max > 0.0 { max * 1.1 } else { 1.0 }
+    }
+
+    /// Volume bars and candlesticks
+    fn draw_series(&self, frame: &mut Frame, viewport: &Viewport, layout: &Layout) {
+        let area = layout.price;
+        let chart_height = area.height;
+        let chart_x = area.x;
+        let chart_y = area.y;
+        let visible = viewport.visible(self.series().len());
+
+        for (pane, pane_area) in &layout.panes {
+            self.draw_pane(frame, viewport, *pane, *pane_area);
+        }
+
+        // Calculate max volume for scaling
+        let max_volume = self.series()[visible.clone()].iter()
+            .map(|c| self.volume_unit.of(c))
+            .fold(f64::MIN, f64::max);
+
+        // Without a pane of their own, volume bars use the bottom 30% of the chart height
+        let volume_max_height = chart_height * 0.3;
+
+        let candle_width = viewport.bar_width(area);
+
+        // Candles may overflow the chart area once the price axis is scaled
+        frame.with_clip(area, |frame| {
+            // Clipped frames start at the area origin, keep chart coordinates
+            frame.translate(Vector::new(-chart_x, -chart_y));
+
+            // Draw volume bars FIRST (so they appear behind candlesticks)
+            if !self.panes.iter().any(|pane| pane.kind == PaneKind::Volume) {
+                for (i, candle) in self.series().iter().enumerate().take(visible.end).skip(visible.start) {
+                    let x = viewport.x(i as f64, area);
+                    let bar_width = candle_width * 0.8;
+
+                    let volume_ratio = (self.volume_unit.of(candle) / max_volume) as f32;
+                    let bar_height = volume_ratio * volume_max_height;
+
+                    // Color volume bars based on candle direction with high transparency
+                    let volume_color = if candle.is_bullish() {
+                        Color { a: 0.2, ..self.style.bullish }
+                    } else {
+                        Color { a: 0.2, ..self.style.bearish }
+                    };
+
+                    let volume_bar = Path::rectangle(
+                        Point::new(x + candle_width * 0.1, chart_y + chart_height - bar_height),
+                        Size::new(bar_width, bar_height),
+                    );
+                    frame.fill(&volume_bar, volume_color);
+                }
+            }
+
+            // Draw the prices on top of volume bars
+            match self.chart_type {
+                ChartType::Candles | ChartType::HeikinAshi => self.draw_candles(frame, viewport, area, false),
+                ChartType::HollowCandles => self.draw_candles(frame, viewport, area, true),
+                ChartType::OhlcBars => self.draw_ohlc_bars(frame, viewport, area),
+                ChartType::Line => self.draw_line(frame, viewport, area, false),
+                ChartType::Area => self.draw_line(frame, viewport, area, true),
+                ChartType::Baseline => self.draw_baseline(frame, viewport, area),
+                ChartType::Renko => self.draw_renko(frame, viewport, area),
+                ChartType::Kagi => self.draw_kagi(frame, viewport, area),
+                ChartType::PointAndFigure => self.draw_point_and_figure(frame, viewport, area),
+            }
+        });
+    }
+
+    /// Indicator lines over the prices, with bands shaded between the outer
+    /// lines and the Ichimoku cloud between its leading spans
+    fn draw_indicators(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let len = self.series().len();
+        let point = |i: usize, value: f64| Point::new(viewport.x(i as f64 + 0.5, area), viewport.y(value, area));
+
+        for overlay in &self.overlays {
+            // Lines drawn ahead of the candles reach into the future margin
+            let bars = len + (0..overlay.lines.len()).map(|line| overlay.overlay.shift(line)).max().unwrap_or(0).max(0) as usize;
+            let visible = viewport.visible(bars);
+            // One bar either side so the lines reach the edges
+            let range = visible.start.saturating_sub(1)..(visible.end + 1).min(bars);
+            let value = |line: usize, i: usize| overlay_value(overlay, line, i);
+
+            match (overlay.overlay, overlay.lines.len()) {
+                (Overlay::Ichimoku { .. }, _) => {
+                    let spans: Vec<(usize, Point, Point)> = range
+                        .clone()
+                        .filter_map(|i| Some((i, point(i, value(2, i)?), point(i, value(3, i)?))))
+                        .collect();
+                    self.draw_cloud(frame, &spans);
+                }
+                (_, 3) => {
+                    let filled: Vec<usize> = range.clone().filter(|i| value(0, *i).is_some() && value(2, *i).is_some()).collect();
+                    if filled.len() > 1 {
+                        let band = Path::new(|builder| {
+                            for (n, i) in filled.iter().enumerate() {
+                                let top = point(*i, value(0, *i).unwrap_or_default());
+                                if n == 0 {
+                                    builder.move_to(top);
+                                } else {
+                                    builder.line_to(top);
+                                }
+                            }
+                            for i in filled.iter().rev() {
+                                builder.line_to(point(*i, value(2, *i).unwrap_or_default()));
+                            }
+                            builder.close();
+                        });
+                        frame.fill(&band, Color { a: 0.08, ..overlay.color });
+                    }
+                }
+                _ => {}
+            }
+
+            for line in 0..overlay.lines.len() {
+                let path = line_path(range.clone().map(|i| value(line, i).map(|value| point(i, value))));
+                frame.stroke(&path, self.overlay_stroke(overlay, line));
+            }
+        }
+    }
+
+    /// Ichimoku cloud between the points of span A and span B at each bar, in
+    /// the bullish color where A is above B and the bearish one elsewhere
+    fn draw_cloud(&self, frame: &mut Frame, spans: &[(usize, Point, Point)]) {
+        let mut bullish = path::Builder::new();
+        let mut bearish = path::Builder::new();
+
+        for pair in spans.windows(2) {
+            let [(i0, a0, b0), (i1, a1, b1)] = [pair[0], pair[1]];
+            if i1 != i0 + 1 {
+                continue;
+            }
+
+            // The y-axis points down, so A is above B where its y is smaller
+            let d0 = b0.y - a0.y;
+            let d1 = b1.y - a1.y;
+            if d0 * d1 >= 0.0 {
+                let builder = if d0 + d1 >= 0.0 { &mut bullish } else { &mut bearish };
+                polygon(builder, &[a0, a1, b1, b0]);
+            } else {
+                // The spans cross between the two bars
+                let t = d0 / (d0 - d1);
+                let cross = Point::new(a0.x + t * (a1.x - a0.x), a0.y + t * (a1.y - a0.y));
+                let (before, after) = if d0 > 0.0 { (&mut bullish, &mut bearish) } else { (&mut bearish, &mut bullish) };
+                polygon(before, &[a0, cross, b0]);
+                polygon(after, &[cross, a1, b1]);
+            }
+        }
+
+        frame.fill(&bullish.build(), Color { a: 0.15, ..self.style.bullish });
+        frame.fill(&bearish.build(), Color { a: 0.15, ..self.style.bearish });
+    }
+
+    /// Stroke of one line of an overlay
+    fn overlay_stroke(&self, overlay: &OverlayLines, line: usize) -> Stroke<'static> {
+        let stroke = Stroke::default().with_width(1.5).with_color(overlay.color);
+        match (overlay.overlay, line) {
+            // Tenkan, Kijun, the spans in the colors of the cloud and a dashed Chikou
+            (Overlay::Ichimoku { .. }, 0) => stroke.with_width(1.0),
+            (Overlay::Ichimoku { .. }, 1) => stroke.with_width(2.0),
+            (Overlay::Ichimoku { .. }, 2) => stroke.with_width(1.0).with_color(self.style.bullish),
+            (Overlay::Ichimoku { .. }, 3) => stroke.with_width(1.0).with_color(self.style.bearish),
+            (Overlay::Ichimoku { .. }, _) => Stroke {
+                line_dash: LineDash { segments: &[4.0, 4.0], offset: 0 },
+                ..stroke.with_width(1.0)
+            },
+            _ => stroke,
+        }
+    }
+
+    /// Volume bars scaled to their own pane, with their moving average
+    fn draw_volume(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle, range: (f64, f64)) {
+        let visible = viewport.visible(self.series().len());
+        let candle_width = viewport.bar_width(area);
+
+        for i in visible.clone() {
+            let candle = &self.series()[i];
+            let x = viewport.x(i as f64, area);
+            let top = value_y(self.volume_unit.of(candle), range, area);
+
+            let color = if candle.is_bullish() {
+                Color { a: 0.5, ..self.style.bullish }
+            } else {
+                Color { a: 0.5, ..self.style.bearish }
+            };
+            let volume_bar = Path::rectangle(
+                Point::new(x + candle_width * 0.1, top),
+                Size::new(candle_width * 0.8, area.y + area.height - top),
+            );
+            frame.fill(&volume_bar, color);
+        }
+
+        if let Some(average) = self.volume_average.lines.first() {
+            self.stroke_values(frame, viewport, area, range, average, self.style.line);
+        }
+    }
+
+    /// Candlesticks, hollow ones outline rising candles and color each candle
+    /// by its close against the previous close
+    fn draw_candles(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle, hollow: bool) {
+        let candle_width = viewport.bar_width(area);
+        let body_width = candle_width * 0.7;
+        let wick_width = candle_width * 0.1;
+
+        for i in viewport.visible(self.series().len()) {
+            let candle = &self.series()[i];
+            let x = viewport.x(i as f64, area) + candle_width / 2.0;
+
+            // Convert prices to screen coordinates (invert Y axis)
+            let open_y = viewport.y(candle.open, area);
+            let close_y = viewport.y(candle.close, area);
+            let high_y = viewport.y(candle.high, area);
+            let low_y = viewport.y(candle.low, area);
+
+            // Determine color based on bullish/bearish
+            let bullish = if hollow {
+                i.checked_sub(1).is_none_or(|previous| candle.close >= self.series()[previous].close)
+            } else {
+                candle.is_bullish()
+            };
+            let color = if bullish { self.style.bullish } else { self.style.bearish };
+
+            // Draw the body (open to close rectangle)
+            let body_top = open_y.min(close_y);
+            let body_height = (open_y - close_y).abs().max(1.0); // Ensure minimum height
+
+            let body = Path::rectangle(
+                Point::new(x - body_width / 2.0, body_top),
+                Size::new(body_width, body_height),
+            );
+
+            if hollow && candle.is_bullish() {
+                // Wicks stop at the outlined body rather than crossing it
+                let upper_wick = Path::line(Point::new(x, high_y), Point::new(x, body_top));
+                let lower_wick = Path::line(Point::new(x, body_top + body_height), Point::new(x, low_y));
+                let stroke = Stroke::default().with_width(wick_width.max(1.0)).with_color(color);
+                frame.stroke(&upper_wick, stroke);
+                frame.stroke(&lower_wick, stroke);
+                frame.stroke(&body, stroke);
+            } else {
+                // Draw the wick (high to low line)
+                let wick = Path::line(Point::new(x, high_y), Point::new(x, low_y));
+                frame.stroke(
+                    &wick,
+                    Stroke::default().with_width(wick_width).with_color(color),
+                );
+
+                frame.fill(&body, color);
+            }
+        }
+    }
+
+    /// OHLC bars: a high-low line with the open ticked left and the close right
+    fn draw_ohlc_bars(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let candle_width = viewport.bar_width(area);
+        let tick_length = candle_width * 0.35;
+        let line_width = (candle_width * 0.1).max(1.0);
+
+        for i in viewport.visible(self.series().len()) {
+            let candle = &self.series()[i];
+            let x = viewport.x(i as f64, area) + candle_width / 2.0;
+            let open_y = viewport.y(candle.open, area);
+            let close_y = viewport.y(candle.close, area);
+
+            let color = if candle.is_bullish() {
+                self.style.bullish
+            } else {
+                self.style.bearish
+            };
+
+            let bar = Path::new(|builder| {
+                builder.move_to(Point::new(x, viewport.y(candle.high, area)));
+                builder.line_to(Point::new(x, viewport.y(candle.low, area)));
+                builder.move_to(Point::new(x - tick_length, open_y));
+                builder.line_to(Point::new(x, open_y));
+                builder.move_to(Point::new(x, close_y));
+                builder.line_to(Point::new(x + tick_length, close_y));
+            });
+            frame.stroke(&bar, Stroke::default().with_width(line_width).with_color(color));
+        }
+    }
+
+    /// Points of the close-price line, one bar past each edge so it runs off the chart
+    fn close_points(&self, viewport: &Viewport, area: Rectangle) -> Vec<Point> {
+        let visible = viewport.visible(self.series().len());
+        let range = visible.start.saturating_sub(1)..(visible.end + 1).min(self.series().len());
+
+        self.series()[range.clone()]
+            .iter()
+            .zip(range)
+            .map(|(candle, i)| Point::new(viewport.x(i as f64 + 0.5, area), viewport.y(candle.close, area)))
+            .collect()
+    }
+
+    /// Close-price line, optionally filled down to the bottom of the chart
+    fn draw_line(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle, filled: bool) {
+        let points = self.close_points(viewport, area);
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return;
+        };
+
+        if filled {
+            let bottom = area.y + area.height;
+            let fill = Path::new(|builder| {
+                builder.move_to(Point::new(first.x, bottom));
+                for point in &points {
+                    builder.line_to(*point);
+                }
+                builder.line_to(Point::new(last.x, bottom));
+                builder.close();
+            });
+            frame.fill(&fill, Color { a: 0.2, ..self.style.line });
+        }
+
+        let line = Path::new(|builder| {
+            builder.move_to(*first);
+            for point in &points[1..] {
+                builder.line_to(*point);
+            }
+        });
+        frame.stroke(&line, Stroke::default().with_width(1.5).with_color(self.style.line));
+    }
+
+    /// Close-price line filled towards the baseline price, bullish above it
+    /// and bearish below
+    fn draw_baseline(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let base_y = viewport.y(self.baseline.unwrap_or(viewport.base), area);
+        let points = self.close_points(viewport, area);
+
+        let base_line = Path::line(Point::new(area.x, base_y), Point::new(area.x + area.width, base_y));
+        frame.stroke(&base_line, Stroke::default().with_width(1.0).with_color(self.style.crosshair));
+
+        for pair in points.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+
+            // Split segments crossing the baseline so each side gets its own color
+            let mut pieces = vec![(from, to)];
+            if (from.y - base_y) * (to.y - base_y) < 0.0 {
+                let t = (base_y - from.y) / (to.y - from.y);
+                let crossing = Point::new(from.x + (to.x - from.x) * t, base_y);
+                pieces = vec![(from, crossing), (crossing, to)];
+            }
+
+            for (a, b) in pieces {
+                // Above the baseline means a smaller y
+                let color = if a.y + b.y <= 2.0 * base_y {
+                    self.style.bullish
+                } else {
+                    self.style.bearish
+                };
+
+                let fill = Path::new(|builder| {
+                    builder.move_to(Point::new(a.x, base_y));
+                    builder.line_to(a);
+                    builder.line_to(b);
+                    builder.line_to(Point::new(b.x, base_y));
+                    builder.close();
+                });
+                frame.fill(&fill, Color { a: 0.2, ..color });
+                frame.stroke(&Path::line(a, b), Stroke::default().with_width(1.5).with_color(color));
+            }
+        }
+    }
+
+    /// Renko bricks, side by side with no wicks
+    fn draw_renko(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let brick_width = viewport.bar_width(area);
+
+        for i in viewport.visible(self.series().len()) {
+            let brick = &self.series()[i];
+            let top = viewport.y(brick.high, area);
+            let bottom = viewport.y(brick.low, area);
+            let color = if brick.is_bullish() {
+                self.style.bullish
+            } else {
+                self.style.bearish
+            };
+
+            let body = Path::rectangle(
+                Point::new(viewport.x(i as f64, area) + brick_width * 0.05, top),
+                Size::new(brick_width * 0.9, (bottom - top).max(1.0)),
+            );
+            frame.fill(&body, Color { a: 0.6, ..color });
+            frame.stroke(&body, Stroke::default().with_width(1.0).with_color(color));
+        }
+    }
+
+    /// Kagi lines: a vertical line per bar joined by horizontal shoulders and waists
+    fn draw_kagi(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let bar_width = viewport.bar_width(area);
+        let visible = viewport.visible(self.series().len());
+
+        // Start one line early so the join into the first visible one shows
+        for i in visible.start.saturating_sub(1)..visible.end {
+            let line = &self.series()[i];
+            let x = viewport.x(i as f64 + 0.5, area);
+            let color = if line.is_bullish() {
+                self.style.bullish
+            } else {
+                self.style.bearish
+            };
+
+            let path = Path::new(|builder| {
+                builder.move_to(Point::new(x, viewport.y(line.open, area)));
+                builder.line_to(Point::new(x, viewport.y(line.close, area)));
+                if i + 1 < self.series().len() {
+                    builder.line_to(Point::new(x + bar_width, viewport.y(line.close, area)));
+                }
+            });
+            frame.stroke(&path, Stroke::default().with_width(2.0).with_color(color));
+        }
+    }
+
+    /// Point-and-Figure columns, an X or O drawn in each box
+    fn draw_point_and_figure(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        let Some(box_size) = self.box_price else {
+            return;
+        };
+        let cell_width = viewport.bar_width(area);
+
+        for i in viewport.visible(self.series().len()) {
+            let column = &self.series()[i];
+            let left = viewport.x(i as f64, area) + cell_width * 0.15;
+            let right = left + cell_width * 0.7;
+            let boxes = ((column.high - column.low) / box_size).round().max(1.0) as usize;
+
+            for b in 0..boxes {
+                let low = column.low + box_size * b as f64;
+                let top = viewport.y(low + box_size, area);
+                let bottom = viewport.y(low, area);
+
+                if column.is_bullish() {
+                    let cross = Path::new(|builder| {
+                        builder.move_to(Point::new(left, top));
+                        builder.line_to(Point::new(right, bottom));
+                        builder.move_to(Point::new(right, top));
+                        builder.line_to(Point::new(left, bottom));
+                    });
+                    frame.stroke(&cross, Stroke::default().with_width(1.5).with_color(self.style.bullish));
+                } else {
+                    let center = Point::new((left + right) / 2.0, (top + bottom) / 2.0);
+                    let radius = ((right - left) / 2.0).min((bottom - top) / 2.0).max(1.0);
+                    frame.stroke(
+                        &Path::circle(center, radius),
+                        Stroke::default().with_width(1.5).with_color(self.style.bearish),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Everything drawn over the series: indicators and the history loading
+    /// indicator
+    fn draw_overlays(&self, frame: &mut Frame, viewport: &Viewport, area: Rectangle) {
+        frame.with_clip(area, |frame| {
+            // Clipped frames start at the area origin, keep chart coordinates
+            frame.translate(Vector::new(-area.x, -area.y));
+            self.draw_indicators(frame, viewport, area);
+        });
+
+        // Draw loading indicator at the left edge while older candles are fetched
+        if self.loading_history {
+            let band_width = 80.0_f32.min(area.width);
+            let band = Path::rectangle(
+                Point::new(area.x, area.y),
+                Size::new(band_width, area.height),
+            );
+            frame.fill(&band, Color::from_rgba(0.3, 0.3, 0.3, 0.4));
+
+            let loading_text = Text {
+                content: "Loading...".to_string(),
+                position: Point::new(area.x + band_width / 2.0, area.y + area.height / 2.0),
+                color: self.style.text,
+                size: 12.0.into(),
+                horizontal_alignment: Horizontal::Center,
+                vertical_alignment: Vertical::Center,
+                ..Default::default()
+            };
+            frame.fill_text(loading_text);
+        }
+    }
+
+    /// Crosshair lines, their axis labels and the info box of the hovered candle
+    fn draw_crosshair(&self, frame: &mut Frame, viewport: &Viewport, layout: &Layout, cursor_pos: Point) {
+        let area = layout.price;
+
+        // Only draw crosshair if cursor is within one of the panes
+        let Some(pane) = layout.all().find(|pane| pane.contains(cursor_pos)) else {
+            return;
+        };
+
+        let crosshair_color = self.style.crosshair;
+
+        // The vertical line runs through every pane
+        let vertical_line = Path::line(
+            Point::new(cursor_pos.x, area.y),
+            Point::new(cursor_pos.x, layout.bottom()),
+        );
+        frame.stroke(
+            &vertical_line,
+            Stroke::default().with_width(1.0).with_color(crosshair_color),
+        );
+
+        // Draw horizontal line
+        let horizontal_line = Path::line(
+            Point::new(pane.x, cursor_pos.y),
+            Point::new(pane.x + pane.width, cursor_pos.y),
+        );
+        frame.stroke(
+            &horizontal_line,
+            Stroke::default().with_width(1.0).with_color(crosshair_color),
+        );
+
+        // Label the price, or volume, under the cursor on the pane's axis
+        let value_label_bg = Path::rectangle(
+            Point::new(pane.x + pane.width, cursor_pos.y - 9.0),
+            Size::new(RIGHT_MARGIN, 18.0),
+        );
+        frame.fill(&value_label_bg, self.style.border);
+
+        let value = match layout.pane_at(cursor_pos) {
+            Some((below, _)) => {
+                let range = self.pane_range(below, viewport);
+                let ratio = ((pane.y + pane.height - cursor_pos.y) / pane.height) as f64;
+                self.pane_label(below.kind, range.0 + ratio * (range.1 - range.0))
+            }
+            None => viewport.label(viewport.price_at(cursor_pos.y, area), self.price_precision),
+        };
+        let value_label = Text {
+            content: value,
+            position: Point::new(pane.x + pane.width + 5.0, cursor_pos.y),
+            color: self.style.text,
+            size: 12.0.into(),
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Center,
+            ..Default::default()
+        };
+        frame.fill_text(value_label);
+
+        // Calculate which candle is under cursor
+        let candle_index = viewport.index_at(cursor_pos.x, area).floor();
+        if candle_index < 0.0 || candle_index as usize >= self.series().len() {
+            return;
+        }
+        let drawn = &self.series()[candle_index as usize];
+        // The real candle behind a time-based bar, bars of other charts are their own
+        let candle = match self.chart_type {
+            ChartType::HeikinAshi => &self.candles[candle_index as usize],
+            _ => drawn,
+        };
+
+        // Format timestamp
+        let time_string = self.timezone.format(candle.timestamp, "%Y-%m-%d %H:%M");
+
+        // Label the candle's time on the time axis
+        let time_label_width = 110.0;
+        let time_label_bg = Path::rectangle(
+            Point::new(cursor_pos.x - time_label_width / 2.0, layout.bottom() + 6.0),
+            Size::new(time_label_width, 18.0),
+        );
+        frame.fill(&time_label_bg, self.style.border);
+
+        let time_label = Text {
+            content: time_string.clone(),
+            position: Point::new(cursor_pos.x, layout.bottom() + 15.0),
+            color: self.style.text,
+            size: 12.0.into(),
+            horizontal_alignment: Horizontal::Center,
+            vertical_alignment: Vertical::Center,
+            ..Default::default()
+        };
+        frame.fill_text(time_label);
+
+        let close = match self.scale_mode {
+            ScaleMode::Percentage => format!("C: {:.*} ({:+.2}%)", self.price_precision, candle.close, viewport.percent(candle.close)),
+            ScaleMode::Linear | ScaleMode::Logarithmic => format!("C: {:.*}", self.price_precision, candle.close),
+        };
+        let mut lines = match self.derived_ends.get(candle_index as usize) {
+            // Bricks and columns cover a varying stretch of time
+            Some(end) if !self.chart_type.is_time_based() => vec![
+                format!("From: {}", time_string),
+                format!("To: {}", self.timezone.format(*end, "%Y-%m-%d %H:%M")),
+            ],
+            _ => vec![format!("Time: {}", time_string)],
+        };
+
+        // Averaged prices are not real ones, list them apart
+        if self.chart_type == ChartType::HeikinAshi {
+            let precision = self.price_precision;
+            lines.push(format!("HA O: {:.*}  H: {:.*}", precision, drawn.open, precision, drawn.high));
+            lines.push(format!("HA L: {:.*}  C: {:.*}", precision, drawn.low, precision, drawn.close));
+        }
+
+        lines.extend([
+            format!("O: {:.*}", self.price_precision, candle.open),
+            format!("H: {:.*}", self.price_precision, candle.high),
+            format!("L: {:.*}", self.price_precision, candle.low),
+            close,
+            match self.volume_unit {
+                VolumeUnit::Base => format!("Vol: {}", axis::abbreviate(candle.volume)),
+                VolumeUnit::Quote => format!("Quote Vol: {}", axis::abbreviate(candle.quote_volume)),
+            },
+        ]);
+
+        // Indicators computed on the bars as drawn
+        for overlay in &self.overlays {
+            let values: Vec<String> = (0..overlay.lines.len())
+                .map(|line| match overlay_value(overlay, line, candle_index as usize) {
+                    Some(value) => format!("{:.*}", self.price_precision, value),
+                    None => "-".to_string(),
+                })
+                .collect();
+            lines.push(format!("{}: {}", overlay.overlay, values.join(" / ")));
+        }
+        for pane in &self.panes {
+            let Some(pane_lines) = self.pane_lines.get(&pane.id).map(|pane| &pane.lines).filter(|lines| !lines.is_empty()) else {
+                continue;
+            };
+            let precision = if pane.kind.thresholds().is_some() { 2 } else { self.price_precision };
+            let values: Vec<String> = pane_lines
+                .iter()
+                .map(|line| match line.get(candle_index as usize).copied().flatten() {
+                    Some(value) => format!("{:.*}", precision, value),
+                    None => "-".to_string(),
+                })
+                .collect();
+            lines.push(format!("{}: {}", pane.kind, values.join(" / ")));
+        }
+
+        draw_info_box(frame, area, &lines);
+    }
+}
+
+/// Moving average of the volume of `candles` in `unit` over `period`, as
+/// the only line, or no lines without a period
+///
+/// Averaged over the whole series so it reads the same however far the
+/// chart is scrolled.
+fn volume_average(candles: &[Candle], unit: VolumeUnit, period: Option<usize>) -> Vec<Vec<Option<f64>>> {
+    let Some(period) = period else {
+        return Vec::new();
+    };
+    let volumes: Vec<f64> = candles.iter().map(|c| unit.of(c)).collect();
+    vec![indicator::sma(&volumes, period)]
+}
+
+/// Put the values at the last candle into `lines`, after the others if it was
+/// `appended`
+fn set_last(lines: &mut [Vec<Option<f64>>], values: Vec<Option<f64>>, appended: bool) {
+    for (line, value) in lines.iter_mut().zip(values) {
+        match line.last_mut() {
+            Some(last) if !appended => *last = value,
+            _ => line.push(value),
+        }
+    }
+}
+
+/// Value of line `line` of an overlay drawn at bar `index`, which may be past
+/// the last candle for lines drawn ahead of it
+fn overlay_value(overlay: &OverlayLines, line: usize, index: usize) -> Option<f64> {
+    let at = index.checked_add_signed(-overlay.overlay.shift(line))?;
+    overlay.lines[line].get(at).copied().flatten()
+}
+
+/// Add the closed polygon through `points` to `builder`
+fn polygon(builder: &mut path::Builder, points: &[Point]) {
+    builder.move_to(points[0]);
+    for point in &points[1..] {
+        builder.line_to(*point);
+    }
+    builder.close();
+}
+
+/// Line through `points`, broken where there is none
+fn line_path(points: impl Iterator<Item = Option<Point>>) -> Path {
+    Path::new(|builder| {
+        let mut started = false;
+        for point in points {
+            match point {
+                Some(point) if started => builder.line_to(point),
+                Some(point) => {
+                    builder.move_to(point);
+                    started = true;
+                }
+                None => started = false,
+            }
+        }
+    })
+}
+
+/// Height of `value` on a pane spanning `range` from bottom to top
+fn value_y(value: f64, range: (f64, f64), area: Rectangle) -> f32 {
+    let ratio = (value - range.0) / (range.1 - range.0);
+    area.y + area.height - ratio as f32 * area.height
+}
+
+/// Box listing `lines` at the top right of the chart area
+fn draw_info_box(frame: &mut Frame, area: Rectangle, lines: &[String]) {
+    let text_size = 12.0;
+    let line_height = 16.0;
+
+    // Create info box at top right
+    // Widen for long lines, at roughly 7px per character
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let info_box_width = (longest as f32 * 7.0 + 20.0).max(240.0);
+    let info_box_height = 20.0 + line_height * lines.len() as f32 - 4.0;
+    let info_box_x = area.x + area.width - info_box_width - 10.0;
+    let info_box_y = area.y + 10.0;
+
+    // Draw semi-transparent background
+    let info_bg = Path::rectangle(
+        Point::new(info_box_x, info_box_y),
+        Size::new(info_box_width, info_box_height),
+    );
+    frame.fill(&info_bg, Color::from_rgba(0.0, 0.0, 0.0, 0.8));
+
+    // Draw border
+    frame.stroke(
+        &info_bg,
+        Stroke::default().with_width(1.0).with_color(Color::from_rgb(0.5, 0.5, 0.5)),
+    );
+
+    let text_color = Color::from_rgb(0.9, 0.9, 0.9);
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_text = Text {
+            content: line.clone(),
+            position: Point::new(info_box_x + 10.0, info_box_y + 10.0 + line_height * i as f32),
+            color: text_color,
+            size: text_size.into(),
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Top,
+            ..Default::default()
+        };
+        frame.fill_text(line_text);
     }
 }
@@ -0,0 +1,1201 @@
+use crate::candlestick::Candle;
+use std::fmt;
+
+/// Simple moving average of `values` over `period`, `None` until a full
+/// period is available
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 {
+        return result;
+    }
+
+    let mut sum = 0.0;
+    for (i, value) in values.iter().enumerate() {
+        sum += value;
+        if i >= period {
+            sum -= values[i - period];
+        }
+        if i + 1 >= period {
+            result[i] = Some(sum / period as f64);
+        }
+    }
+
+    result
+}
+
+/// Exponential moving average of `values` over `period`, seeded with the
+/// simple average of the first period
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return result;
+    }
+
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut average = values[..period].iter().sum::<f64>() / period as f64;
+    result[period - 1] = Some(average);
+
+    for (i, value) in values.iter().enumerate().skip(period) {
+        average += alpha * (value - average);
+        result[i] = Some(average);
+    }
+
+    result
+}
+
+/// Weighted moving average of `values` over `period`, the latest value
+/// weighing `period` times as much as the oldest
+pub fn wma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 {
+        return result;
+    }
+
+    let weights = (period * (period + 1)) as f64 / 2.0;
+    for i in period.saturating_sub(1)..values.len() {
+        let window = &values[i + 1 - period..=i];
+        let weighted: f64 = window.iter().enumerate().map(|(w, value)| (w + 1) as f64 * value).sum();
+        result[i] = Some(weighted / weights);
+    }
+
+    result
+}
+
+/// Bollinger Bands of `values`: the simple moving average over `period` and
+/// `width` standard deviations either side of it
+pub fn bollinger(values: &[f64], period: usize, width: f64) -> Bands {
+    let middle = sma(values, period);
+    let deviation: Vec<Option<f64>> = middle
+        .iter()
+        .enumerate()
+        .map(|(i, mean)| {
+            let mean = (*mean)?;
+            let window = &values[i + 1 - period..=i];
+            let variance = window.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / period as f64;
+            Some(variance.sqrt())
+        })
+        .collect();
+
+    let band = |sign: f64| -> Vec<Option<f64>> {
+        middle
+            .iter()
+            .zip(&deviation)
+            .map(|(mean, deviation)| Some(mean.as_ref()? + sign * width * deviation.as_ref()?))
+            .collect()
+    };
+
+    Bands {
+        upper: band(1.0),
+        lower: band(-1.0),
+        middle,
+    }
+}
+
+/// Relative strength index of `values` over `period`, from 0 to 100
+///
+/// Gains and losses are averaged the Wilder way, starting from their simple
+/// averages over the first `period` changes.
+pub fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; values.len()];
+    if period == 0 || values.len() <= period {
+        return result;
+    }
+
+    let changes: Vec<f64> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    result[period] = Some(relative_strength(gain, loss));
+
+    for (i, change) in changes.iter().enumerate().skip(period) {
+        gain = (gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+        result[i + 1] = Some(relative_strength(gain, loss));
+    }
+
+    result
+}
+
+/// RSI of an average gain and loss, 100 when nothing was lost
+pub(crate) fn relative_strength(gain: f64, loss: f64) -> f64 {
+    if loss == 0.0 {
+        if gain == 0.0 { 50.0 } else { 100.0 }
+    } else {
+        100.0 - 100.0 / (1.0 + gain / loss)
+    }
+}
+
+/// Moving average convergence divergence: the `fast` EMA minus the `slow`
+/// one, its EMA over `signal` and the difference between the two
+pub fn macd(values: &[f64], fast: usize, slow: usize, signal: usize) -> MacdLines {
+    let fast = ema(values, fast);
+    let slow = ema(values, slow);
+    let line: Vec<Option<f64>> = fast
+        .iter()
+        .zip(&slow)
+        .map(|(fast, slow)| Some((*fast)? - (*slow)?))
+        .collect();
+
+    // The signal starts a full period after the line does
+    let start = line.iter().position(Option::is_some).unwrap_or(line.len());
+    let defined: Vec<f64> = line[start..].iter().flatten().copied().collect();
+    let mut signal_line = vec![None; start];
+    signal_line.extend(ema(&defined, signal));
+
+    let histogram = line
+        .iter()
+        .zip(&signal_line)
+        .map(|(line, signal)| Some((*line)? - (*signal)?))
+        .collect();
+
+    MacdLines {
+        line,
+        signal: signal_line,
+        histogram,
+    }
+}
+
+/// Lines of [`macd`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacdLines {
+    pub line: Vec<Option<f64>>,
+    pub signal: Vec<Option<f64>>,
+    pub histogram: Vec<Option<f64>>,
+}
+
+/// Stochastic oscillator of `candles`: where the close sits in the range of
+/// the last `period` candles, from 0 to 100
+///
+/// %K is that position averaged over `smooth` candles (1 for the fast
+/// stochastic) and %D the average of %K over `signal` candles.
+pub fn stochastic(candles: &[Candle], period: usize, smooth: usize, signal: usize) -> StochasticLines {
+    let mut raw = vec![None; candles.len()];
+    if period > 0 {
+        for i in period.saturating_sub(1)..candles.len() {
+            let window = &candles[i + 1 - period..=i];
+            let high = window.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+            let low = window.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+            raw[i] = Some(if high > low { 100.0 * (candles[i].close - low) / (high - low) } else { 50.0 });
+        }
+    }
+
+    let k = sma_defined(&raw, smooth);
+    let d = sma_defined(&k, signal);
+    StochasticLines { k, d }
+}
+
+/// Lines of [`stochastic`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StochasticLines {
+    pub k: Vec<Option<f64>>,
+    pub d: Vec<Option<f64>>,
+}
+
+/// Simple moving average of a series that starts with missing values
+fn sma_defined(values: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    let start = values.iter().position(Option::is_some).unwrap_or(values.len());
+    let defined: Vec<f64> = values[start..].iter().flatten().copied().collect();
+    let mut result = vec![None; start];
+    result.extend(sma(&defined, period));
+    result
+}
+
+/// Average true range of `candles` over `period`, smoothed the Wilder way
+///
+/// True ranges start at the second candle, which has a close to compare with,
+/// so the first value is at index `period`.
+pub fn atr(candles: &[Candle], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; candles.len()];
+    if period == 0 || candles.len() <= period {
+        return result;
+    }
+
+    let true_ranges: Vec<f64> = candles.windows(2).map(|pair| true_range(&pair[1], &pair[0])).collect();
+    let mut average = true_ranges[..period].iter().sum::<f64>() / period as f64;
+    result[period] = Some(average);
+
+    for (i, tr) in true_ranges.iter().enumerate().skip(period) {
+        average = (average * (period - 1) as f64 + tr) / period as f64;
+        result[i + 1] = Some(average);
+    }
+
+    result
+}
+
+/// Range of `candle` including any gap from the `previous` close
+pub(crate) fn true_range(candle: &Candle, previous: &Candle) -> f64 {
+    (candle.high - candle.low)
+        .max((candle.high - previous.close).abs())
+        .max((candle.low - previous.close).abs())
+}
+
+/// Ichimoku Kinko Hyo of `candles`: midpoints of the high and low over the
+/// `tenkan`, `kijun` and `senkou` periods
+///
+/// Every line is given at the candle it is computed from; the leading spans
+/// are drawn `kijun` bars ahead and the Chikou span, the close, `kijun` bars
+/// behind, see [`Overlay::shift`].
+pub fn ichimoku(candles: &[Candle], tenkan: usize, kijun: usize, senkou: usize) -> IchimokuLines {
+    let tenkan = midpoint(candles, tenkan);
+    let kijun = midpoint(candles, kijun);
+    let senkou_a = tenkan
+        .iter()
+        .zip(&kijun)
+        .map(|(tenkan, kijun)| Some(((*tenkan)? + (*kijun)?) / 2.0))
+        .collect();
+
+    IchimokuLines {
+        senkou_a,
+        senkou_b: midpoint(candles, senkou),
+        chikou: candles.iter().map(|c| Some(c.close)).collect(),
+        tenkan,
+        kijun,
+    }
+}
+
+/// Lines of [`ichimoku`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IchimokuLines {
+    pub tenkan: Vec<Option<f64>>,
+    pub kijun: Vec<Option<f64>>,
+    pub senkou_a: Vec<Option<f64>>,
+    pub senkou_b: Vec<Option<f64>>,
+    pub chikou: Vec<Option<f64>>,
+}
+
+/// Middle of the highest high and lowest low of the last `period` candles
+fn midpoint(candles: &[Candle], period: usize) -> Vec<Option<f64>> {
+    let mut result = vec![None; candles.len()];
+    if period == 0 {
+        return result;
+    }
+
+    for i in period.saturating_sub(1)..candles.len() {
+        let window = &candles[i + 1 - period..=i];
+        let high = window.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+        let low = window.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+        result[i] = Some((high + low) / 2.0);
+    }
+
+    result
+}
+
+/// Volume-weighted average of typical prices, starting over every `session`
+///
+/// With a `width`, the upper and lower bands are that many volume-weighted
+/// standard deviations either side, otherwise they have no values.
+pub fn vwap(candles: &[Candle], session: Session, width: Option<f64>) -> Bands {
+    vwap_from(candles, Reset::Session(session), width)
+}
+
+/// VWAP of the candles from the one opening at `anchor`, a Unix timestamp in
+/// seconds, see [`vwap`]
+pub fn anchored_vwap(candles: &[Candle], anchor: i64, width: Option<f64>) -> Bands {
+    vwap_from(candles, Reset::Anchor(anchor), width)
+}
+
+fn vwap_from(candles: &[Candle], reset: Reset, width: Option<f64>) -> Bands {
+    let mut state = VwapStep::new(reset, width);
+    let values: Vec<[Option<f64>; 3]> = candles.iter().map(|candle| state.step(candle)).collect();
+    Bands {
+        upper: values.iter().map(|v| v[0]).collect(),
+        middle: values.iter().map(|v| v[1]).collect(),
+        lower: values.iter().map(|v| v[2]).collect(),
+    }
+}
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// When a session VWAP starts over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Session {
+    /// At midnight UTC
+    #[default]
+    Day,
+    /// At midnight UTC on Mondays
+    Week,
+    /// Every `length` seconds, counted from `offset` seconds past the Unix
+    /// epoch, e.g. 13:30 UTC daily is a length of 86400 and an offset of 48600
+    Custom { length: i64, offset: i64 },
+}
+
+impl Session {
+    /// Start of the session `timestamp` falls in
+    pub fn start(self, timestamp: i64) -> i64 {
+        let (length, offset) = match self {
+            Session::Day => (DAY, 0),
+            Session::Week => (7 * DAY, 4 * DAY), // The epoch was a Thursday
+            Session::Custom { length, offset } => (length.max(1), offset),
+        };
+        (timestamp - offset).div_euclid(length) * length + offset
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Session::Day => write!(f, "Day"),
+            Session::Week => write!(f, "Week"),
+            Session::Custom { length, offset } => {
+                // Offsets a whole session apart start the same sessions
+                let start = offset.rem_euclid((*length).max(1));
+                write!(f, "{}h from {:02}:{:02}", *length as f64 / 3600.0, start / 3600, start % 3600 / 60)
+            }
+        }
+    }
+}
+
+// Where a VWAP starts accumulating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reset {
+    Session(Session),
+    Anchor(i64),
+}
+
+impl Reset {
+    /// Start of the run of candles `timestamp` belongs to, `None` before an anchor
+    fn start(self, timestamp: i64) -> Option<i64> {
+        match self {
+            Reset::Session(session) => Some(session.start(timestamp)),
+            Reset::Anchor(anchor) => (timestamp >= anchor).then_some(anchor),
+        }
+    }
+}
+
+/// Upper, middle and lower lines of a channel around the price
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bands {
+    pub upper: Vec<Option<f64>>,
+    pub middle: Vec<Option<f64>>,
+    pub lower: Vec<Option<f64>>,
+}
+
+/// Indicator drawn over the prices
+///
+/// Moving averages and Bollinger Bands are computed from closes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overlay {
+    Sma(usize),
+    Ema(usize),
+    Wma(usize),
+    /// Bands `width` standard deviations around the simple moving average
+    Bollinger { period: usize, width: f64 },
+    /// Volume-weighted average price of each session, with bands `bands`
+    /// standard deviations either side when set
+    Vwap { session: Session, bands: Option<f64> },
+    /// VWAP from the candle opening at `anchor`, a Unix timestamp in seconds
+    AnchoredVwap { anchor: i64, bands: Option<f64> },
+    /// Tenkan, Kijun, Senkou A and B and Chikou, see [`ichimoku`]
+    Ichimoku { tenkan: usize, kijun: usize, senkou: usize },
+}
+
+impl Overlay {
+    /// Running state for live updates, through `candles`
+    pub(crate) fn tracker(self, candles: &[Candle]) -> Tracker {
+        match self {
+            Overlay::Ema(period) => Tracker::Ema(Ema::new(period)).replay(candles),
+            Overlay::Sma(period) | Overlay::Wma(period) | Overlay::Bollinger { period, .. } => Tracker::Window(period),
+            Overlay::Vwap { session, bands } => Tracker::Vwap(Vwap::new(session, bands)).replay(candles),
+            Overlay::AnchoredVwap { anchor, bands } => Tracker::Vwap(Vwap::anchored(anchor, bands)).replay(candles),
+            Overlay::Ichimoku { tenkan, kijun, senkou } => Tracker::Window(tenkan.max(kijun).max(senkou)),
+        }
+    }
+
+    /// Bars `line` is drawn ahead of the candle it is computed at, negative
+    /// for behind
+    pub fn shift(self, line: usize) -> isize {
+        match (self, line) {
+            (Overlay::Ichimoku { kijun, .. }, 2 | 3) => kijun as isize,
+            (Overlay::Ichimoku { kijun, .. }, 4) => -(kijun as isize),
+            _ => 0,
+        }
+    }
+
+    /// Lines of the indicator, each with a value or `None` for every candle
+    ///
+    /// Computed over the whole series so values at the left edge of the view
+    /// take earlier candles into account.
+    pub fn compute(self, candles: &[Candle]) -> Vec<Vec<Option<f64>>> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        match self {
+            Overlay::Sma(period) => vec![sma(&closes, period)],
+            Overlay::Ema(period) => vec![ema(&closes, period)],
+            Overlay::Wma(period) => vec![wma(&closes, period)],
+            Overlay::Bollinger { period, width } => {
+                let bands = bollinger(&closes, period, width);
+                vec![bands.upper, bands.middle, bands.lower]
+            }
+            Overlay::Vwap { session, bands: width } => band_lines(vwap(candles, session, width), width.is_some()),
+            Overlay::AnchoredVwap { anchor, bands: width } => {
+                band_lines(anchored_vwap(candles, anchor, width), width.is_some())
+            }
+            Overlay::Ichimoku { tenkan, kijun, senkou } => {
+                let lines = ichimoku(candles, tenkan, kijun, senkou);
+                vec![lines.tenkan, lines.kijun, lines.senkou_a, lines.senkou_b, lines.chikou]
+            }
+        }
+    }
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Overlay::Sma(period) => write!(f, "SMA {}", period),
+            Overlay::Ema(period) => write!(f, "EMA {}", period),
+            Overlay::Wma(period) => write!(f, "WMA {}", period),
+            Overlay::Bollinger { period, width } => write!(f, "BB {} {}", period, width),
+            Overlay::Vwap { session, bands } => write!(f, "VWAP {}{}", session, BandWidth(*bands)),
+            Overlay::AnchoredVwap { bands, .. } => write!(f, "AVWAP{}", BandWidth(*bands)),
+            Overlay::Ichimoku { tenkan, kijun, senkou } => write!(f, "Ichimoku {} {} {}", tenkan, kijun, senkou),
+        }
+    }
+}
+
+// Lines of a VWAP, the bands only when they have values
+fn band_lines(bands: Bands, with_bands: bool) -> Vec<Vec<Option<f64>>> {
+    if with_bands {
+        vec![bands.upper, bands.middle, bands.lower]
+    } else {
+        vec![bands.middle]
+    }
+}
+
+// Width of optional bands in an overlay name, e.g. " ±2σ"
+struct BandWidth(Option<f64>);
+
+impl fmt::Display for BandWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(width) => write!(f, " ±{}σ", width),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An indicator following a live series one candle at a time, in constant
+/// time per candle
+///
+/// The state covers every candle but the last, which may still change, so
+/// replacing it only redoes one step.
+pub trait Incremental {
+    type Output;
+
+    /// Add a candle after the last one, returning the values at it
+    fn update(&mut self, candle: &Candle) -> Self::Output;
+
+    /// Change the last candle, such as the forming candle of a live stream,
+    /// returning the values at it
+    fn replace_last(&mut self, candle: &Candle) -> Self::Output;
+}
+
+// State of an indicator small enough to copy, advanced by a candle at a time
+trait Step: Copy {
+    type Output;
+
+    fn step(&mut self, candle: &Candle) -> Self::Output;
+}
+
+// Steps through candles, keeping the state before the last one
+#[derive(Debug, Clone, Copy)]
+struct Stepper<S> {
+    before_last: S,
+    last: Option<Candle>,
+}
+
+impl<S: Step> Stepper<S> {
+    fn new(state: S) -> Self {
+        Self { before_last: state, last: None }
+    }
+
+    fn update(&mut self, candle: &Candle) -> S::Output {
+        if let Some(last) = self.last {
+            self.before_last.step(&last);
+        }
+        self.replace_last(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> S::Output {
+        self.last = Some(*candle);
+        let mut state = self.before_last;
+        state.step(candle)
+    }
+}
+
+// Exponential average of a value at a time, see `ema`
+#[derive(Debug, Clone, Copy)]
+struct EmaStep {
+    period: usize,
+    count: usize,
+    average: f64, // Sum of the values until a full period is in
+}
+
+impl EmaStep {
+    fn new(period: usize) -> Self {
+        Self { period, count: 0, average: 0.0 }
+    }
+
+    fn next(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+
+        self.count += 1;
+        if self.count < self.period {
+            self.average += value;
+            return None;
+        }
+
+        if self.count == self.period {
+            self.average = (self.average + value) / self.period as f64;
+        } else {
+            self.average += 2.0 / (self.period as f64 + 1.0) * (value - self.average);
+        }
+        Some(self.average)
+    }
+}
+
+impl Step for EmaStep {
+    type Output = Option<f64>;
+
+    fn step(&mut self, candle: &Candle) -> Option<f64> {
+        self.next(candle.close)
+    }
+}
+
+/// Exponential moving average of closes, see [`ema`]
+#[derive(Debug, Clone, Copy)]
+pub struct Ema(Stepper<EmaStep>);
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Self(Stepper::new(EmaStep::new(period)))
+    }
+}
+
+impl Incremental for Ema {
+    type Output = Option<f64>;
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.update(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.replace_last(candle)
+    }
+}
+
+// Wilder averages of gains and losses, see `rsi`
+#[derive(Debug, Clone, Copy)]
+struct RsiStep {
+    period: usize,
+    count: usize, // Changes seen
+    previous: Option<f64>,
+    gain: f64,
+    loss: f64,
+}
+
+impl Step for RsiStep {
+    type Output = Option<f64>;
+
+    fn step(&mut self, candle: &Candle) -> Option<f64> {
+        let previous = self.previous.replace(candle.close)?;
+        if self.period == 0 {
+            return None;
+        }
+
+        let change = candle.close - previous;
+        let period = self.period as f64;
+        self.count += 1;
+        if self.count <= self.period {
+            // Simple averages over the first period
+            self.gain += change.max(0.0) / period;
+            self.loss += (-change).max(0.0) / period;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.gain = (self.gain * (period - 1.0) + change.max(0.0)) / period;
+            self.loss = (self.loss * (period - 1.0) + (-change).max(0.0)) / period;
+        }
+        Some(relative_strength(self.gain, self.loss))
+    }
+}
+
+/// Relative strength index of closes, see [`rsi`]
+#[derive(Debug, Clone, Copy)]
+pub struct Rsi(Stepper<RsiStep>);
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self(Stepper::new(RsiStep {
+            period,
+            count: 0,
+            previous: None,
+            gain: 0.0,
+            loss: 0.0,
+        }))
+    }
+}
+
+impl Incremental for Rsi {
+    type Output = Option<f64>;
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.update(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.replace_last(candle)
+    }
+}
+
+// Fast, slow and signal averages, see `macd`
+#[derive(Debug, Clone, Copy)]
+struct MacdStep {
+    fast: EmaStep,
+    slow: EmaStep,
+    signal: EmaStep,
+}
+
+impl Step for MacdStep {
+    type Output = [Option<f64>; 3];
+
+    fn step(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        let fast = self.fast.next(candle.close);
+        let slow = self.slow.next(candle.close);
+        let line = fast.zip(slow).map(|(fast, slow)| fast - slow);
+        let signal = line.and_then(|line| self.signal.next(line));
+        let histogram = line.zip(signal).map(|(line, signal)| line - signal);
+        [line, signal, histogram]
+    }
+}
+
+/// MACD line, signal and histogram of closes, see [`macd`]
+#[derive(Debug, Clone, Copy)]
+pub struct Macd(Stepper<MacdStep>);
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self(Stepper::new(MacdStep {
+            fast: EmaStep::new(fast),
+            slow: EmaStep::new(slow),
+            signal: EmaStep::new(signal),
+        }))
+    }
+}
+
+impl Incremental for Macd {
+    type Output = [Option<f64>; 3];
+
+    fn update(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        self.0.update(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        self.0.replace_last(candle)
+    }
+}
+
+// Wilder average of true ranges, see `atr`
+#[derive(Debug, Clone, Copy)]
+struct AtrStep {
+    period: usize,
+    count: usize, // True ranges seen
+    previous: Option<Candle>,
+    average: f64,
+}
+
+impl Step for AtrStep {
+    type Output = Option<f64>;
+
+    fn step(&mut self, candle: &Candle) -> Option<f64> {
+        let previous = self.previous.replace(*candle)?;
+        if self.period == 0 {
+            return None;
+        }
+
+        let tr = true_range(candle, &previous);
+        let period = self.period as f64;
+        self.count += 1;
+        if self.count <= self.period {
+            self.average += tr / period;
+            if self.count < self.period {
+                return None;
+            }
+        } else {
+            self.average = (self.average * (period - 1.0) + tr) / period;
+        }
+        Some(self.average)
+    }
+}
+
+/// Average true range, see [`atr`]
+#[derive(Debug, Clone, Copy)]
+pub struct Atr(Stepper<AtrStep>);
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self(Stepper::new(AtrStep {
+            period,
+            count: 0,
+            previous: None,
+            average: 0.0,
+        }))
+    }
+}
+
+impl Incremental for Atr {
+    type Output = Option<f64>;
+
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.update(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> Option<f64> {
+        self.0.replace_last(candle)
+    }
+}
+
+// Running sums of a VWAP since the start of its session or anchor
+#[derive(Debug, Clone, Copy)]
+struct VwapStep {
+    reset: Reset,
+    width: Option<f64>,
+    start: Option<i64>,
+    volume: f64,
+    price_volume: f64,  // Typical price times volume
+    square_volume: f64, // Squared typical price times volume
+}
+
+impl VwapStep {
+    fn new(reset: Reset, width: Option<f64>) -> Self {
+        Self {
+            reset,
+            width,
+            start: None,
+            volume: 0.0,
+            price_volume: 0.0,
+            square_volume: 0.0,
+        }
+    }
+}
+
+impl Step for VwapStep {
+    type Output = [Option<f64>; 3];
+
+    fn step(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        let Some(start) = self.reset.start(candle.timestamp) else {
+            return [None; 3];
+        };
+        if self.start != Some(start) {
+            *self = Self {
+                start: Some(start),
+                ..Self::new(self.reset, self.width)
+            };
+        }
+
+        let price = candle.typical_price();
+        self.volume += candle.volume;
+        self.price_volume += price * candle.volume;
+        self.square_volume += price * price * candle.volume;
+        if self.volume <= 0.0 {
+            return [None; 3];
+        }
+
+        let mean = self.price_volume / self.volume;
+        let Some(width) = self.width else {
+            return [None, Some(mean), None];
+        };
+        let deviation = (self.square_volume / self.volume - mean * mean).max(0.0).sqrt();
+        [Some(mean + width * deviation), Some(mean), Some(mean - width * deviation)]
+    }
+}
+
+/// Upper band, VWAP and lower band, see [`vwap`] and [`anchored_vwap`]
+#[derive(Debug, Clone, Copy)]
+pub struct Vwap(Stepper<VwapStep>);
+
+impl Vwap {
+    pub fn new(session: Session, width: Option<f64>) -> Self {
+        Self(Stepper::new(VwapStep::new(Reset::Session(session), width)))
+    }
+
+    pub fn anchored(anchor: i64, width: Option<f64>) -> Self {
+        Self(Stepper::new(VwapStep::new(Reset::Anchor(anchor), width)))
+    }
+
+    fn has_bands(&self) -> bool {
+        self.0.before_last.width.is_some()
+    }
+}
+
+impl Incremental for Vwap {
+    type Output = [Option<f64>; 3];
+
+    fn update(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        self.0.update(candle)
+    }
+
+    fn replace_last(&mut self, candle: &Candle) -> [Option<f64>; 3] {
+        self.0.replace_last(candle)
+    }
+}
+
+/// Keeps the lines of an overlay or pane up to date as candles come in
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Tracker {
+    Ema(Ema),
+    Rsi(Rsi),
+    Macd(Macd),
+    Atr(Atr),
+    Vwap(Vwap),
+    /// Values depend on this many candles at most, recomputed from them
+    Window(usize),
+}
+
+impl Tracker {
+    /// Tracker already through `candles`
+    pub fn replay(mut self, candles: &[Candle]) -> Self {
+        for candle in candles {
+            match &mut self {
+                Tracker::Ema(ema) => {
+                    ema.update(candle);
+                }
+                Tracker::Rsi(rsi) => {
+                    rsi.update(candle);
+                }
+                Tracker::Macd(macd) => {
+                    macd.update(candle);
+                }
+                Tracker::Atr(atr) => {
+                    atr.update(candle);
+                }
+                Tracker::Vwap(vwap) => {
+                    vwap.update(candle);
+                }
+                Tracker::Window(_) => break,
+            }
+        }
+        self
+    }
+
+    /// Values of each line at the last of `candles`, which was just added
+    /// (`appended`) or changed, `compute` giving the lines of a whole series
+    pub fn next(
+        &mut self,
+        candles: &[Candle],
+        appended: bool,
+        compute: impl Fn(&[Candle]) -> Vec<Vec<Option<f64>>>,
+    ) -> Vec<Option<f64>> {
+        let Some(candle) = candles.last() else {
+            return Vec::new();
+        };
+
+        match self {
+            Tracker::Ema(ema) if appended => vec![ema.update(candle)],
+            Tracker::Ema(ema) => vec![ema.replace_last(candle)],
+            Tracker::Rsi(rsi) if appended => vec![rsi.update(candle)],
+            Tracker::Rsi(rsi) => vec![rsi.replace_last(candle)],
+            Tracker::Macd(macd) if appended => macd.update(candle).to_vec(),
+            Tracker::Macd(macd) => macd.replace_last(candle).to_vec(),
+            Tracker::Atr(atr) if appended => vec![atr.update(candle)],
+            Tracker::Atr(atr) => vec![atr.replace_last(candle)],
+            Tracker::Vwap(vwap) => {
+                let values = if appended { vwap.update(candle) } else { vwap.replace_last(candle) };
+                if vwap.has_bands() { values.to_vec() } else { vec![values[1]] }
+            }
+            Tracker::Window(lookback) => compute(&candles[candles.len().saturating_sub(*lookback)..])
+                .into_iter()
+                .map(|line| line.last().copied().flatten())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[Option<f64>], expected: &[Option<f64>]) {
+        assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+        for (actual, expected) in actual.iter().zip(expected) {
+            match (actual, expected) {
+                (Some(a), Some(e)) => assert!((a - e).abs() < 1e-3, "{:?} != {:?}", actual, expected),
+                (None, None) => {}
+                _ => panic!("{:?} != {:?}", actual, expected),
+            }
+        }
+    }
+
+    fn candle(high: f64, low: f64, close: f64) -> Candle {
+        Candle::new(0, close, high, low, close, 1.0)
+    }
+
+    #[test]
+    fn sma_of_small_period() {
+        assert_close(&sma(&[1.0, 2.0, 3.0, 4.0, 6.0], 3), &[None, None, Some(2.0), Some(3.0), Some(13.0 / 3.0)]);
+    }
+
+    #[test]
+    fn ema_starts_from_the_sma() {
+        // Seeded with the SMA of the first 3, then alpha = 2 / (3 + 1)
+        let result = ema(&[2.0, 4.0, 6.0, 5.0, 9.0], 3);
+        assert_close(&result, &[None, None, Some(4.0), Some(4.5), Some(6.75)]);
+    }
+
+    #[test]
+    fn wma_weights_the_latest_most() {
+        // Weights 1, 2, 3 over a sum of 6
+        let result = wma(&[1.0, 2.0, 3.0, 4.0, 10.0], 3);
+        assert_close(&result, &[None, None, Some(14.0 / 6.0), Some(20.0 / 6.0), Some(41.0 / 6.0)]);
+    }
+
+    #[test]
+    fn bollinger_uses_the_population_deviation() {
+        // Mean 5 and population standard deviation 2 (the sample one is 2.14)
+        let result = bollinger(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 8, 2.0);
+
+        let mut expected = vec![None; 7];
+        expected.push(Some(5.0));
+        assert_close(&result.middle, &expected);
+        expected[7] = Some(9.0);
+        assert_close(&result.upper, &expected);
+        expected[7] = Some(1.0);
+        assert_close(&result.lower, &expected);
+    }
+
+    #[test]
+    fn averages_need_a_whole_period() {
+        let values = [1.0, 2.0];
+        for period in [0, 3] {
+            assert_close(&sma(&values, period), &[None, None]);
+            assert_close(&ema(&values, period), &[None, None]);
+            assert_close(&wma(&values, period), &[None, None]);
+
+            let bands = bollinger(&values, period, 2.0);
+            assert_close(&bands.upper, &[None, None]);
+            assert_close(&bands.middle, &[None, None]);
+            assert_close(&bands.lower, &[None, None]);
+        }
+    }
+
+    #[test]
+    fn rsi_matches_wilder_example() {
+        // The 14-period example from StockCharts, whose spreadsheet rounds the
+        // averages (70.53, 66.32); unrounded the first average gain is 3.34 / 14
+        // and loss 1.40 / 14
+        let closes = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61, 46.28, 46.28,
+            46.00, 46.03, 46.41, 46.22, 45.64, 46.21,
+        ];
+        let result = rsi(&closes, 14);
+
+        let mut expected = vec![None; 14];
+        expected.extend([70.464, 66.249, 66.481, 69.347, 66.295, 57.915, 62.881].map(Some));
+        assert_close(&result, &expected);
+    }
+
+    #[test]
+    fn rsi_saturates_without_losses() {
+        let result = rsi(&[1.0, 2.0, 3.0, 4.0], 2);
+        assert_close(&result, &[None, None, Some(100.0), Some(100.0)]);
+    }
+
+    #[test]
+    fn macd_of_small_periods() {
+        // EMA 2: 1.5, 2.5, 3.5, 4.5, 13/6 and EMA 3: 2, 3, 4, 2.5
+        let result = macd(&[1.0, 2.0, 3.0, 4.0, 5.0, 1.0], 2, 3, 2);
+
+        assert_close(&result.line, &[None, None, Some(0.5), Some(0.5), Some(0.5), Some(-1.0 / 3.0)]);
+        assert_close(&result.signal, &[None, None, None, Some(0.5), Some(0.5), Some(-1.0 / 18.0)]);
+        assert_close(&result.histogram, &[None, None, None, Some(0.0), Some(0.0), Some(-5.0 / 18.0)]);
+    }
+
+    #[test]
+    fn macd_of_a_straight_line_is_constant() {
+        // An EMA over n lags a straight line by (n - 1) / 2 steps, so on a
+        // slope of 1 the 12/26 MACD is (25 - 11) / 2
+        let values: Vec<f64> = (0..60).map(f64::from).collect();
+        let result = macd(&values, 12, 26, 9);
+
+        assert!(result.line[24].is_none());
+        assert!(result.line[25..].iter().all(|v| v.is_some_and(|v| (v - 7.0).abs() < 1e-9)));
+        assert!(result.signal[32].is_none());
+        assert!(result.histogram[33..].iter().all(|v| v.is_some_and(|v| v.abs() < 1e-9)));
+    }
+
+    #[test]
+    fn stochastic_of_small_periods() {
+        let candles = [
+            candle(10.0, 8.0, 9.0),
+            candle(11.0, 9.0, 10.0),
+            candle(12.0, 10.0, 11.0),
+            candle(11.0, 9.0, 9.0),
+            candle(10.0, 7.0, 8.0),
+        ];
+
+        let fast = stochastic(&candles, 3, 1, 2);
+        assert_close(&fast.k, &[None, None, Some(75.0), Some(0.0), Some(20.0)]);
+        assert_close(&fast.d, &[None, None, None, Some(37.5), Some(10.0)]);
+
+        let slow = stochastic(&candles, 3, 2, 2);
+        assert_close(&slow.k, &[None, None, None, Some(37.5), Some(10.0)]);
+        assert_close(&slow.d, &[None, None, None, None, Some(23.75)]);
+    }
+
+    #[test]
+    fn atr_includes_gaps() {
+        // True ranges 2, 3, 1 and 4.5 for the gap up
+        let candles = [
+            candle(10.0, 8.0, 9.0),
+            candle(11.0, 9.0, 10.0),
+            candle(13.0, 10.0, 12.0),
+            candle(12.0, 11.0, 11.5),
+            candle(16.0, 15.0, 15.5),
+        ];
+
+        let result = atr(&candles, 2);
+        assert_close(&result, &[None, None, Some(2.5), Some(1.75), Some(3.125)]);
+    }
+
+    #[test]
+    fn ichimoku_of_small_periods() {
+        let candles = [
+            candle(10.0, 8.0, 9.0),
+            candle(11.0, 9.0, 10.0),
+            candle(13.0, 10.0, 12.0),
+            candle(12.0, 11.0, 11.5),
+        ];
+
+        let lines = ichimoku(&candles, 1, 2, 3);
+        assert_close(&lines.tenkan, &[Some(9.0), Some(10.0), Some(11.5), Some(11.5)]);
+        assert_close(&lines.kijun, &[None, Some(9.5), Some(11.0), Some(11.5)]);
+        assert_close(&lines.senkou_a, &[None, Some(9.75), Some(11.25), Some(11.5)]);
+        assert_close(&lines.senkou_b, &[None, None, Some(10.5), Some(11.0)]);
+        assert_close(&lines.chikou, &[Some(9.0), Some(10.0), Some(12.0), Some(11.5)]);
+
+        let overlay = Overlay::Ichimoku { tenkan: 9, kijun: 26, senkou: 52 };
+        assert_eq!((0..5).map(|line| overlay.shift(line)).collect::<Vec<_>>(), [0, 0, 26, 26, -26]);
+    }
+
+    #[test]
+    fn vwap_starts_over_each_session() {
+        let flat = |timestamp: i64, price: f64, volume: f64| Candle::new(timestamp, price, price, price, price, volume);
+        let candles = [flat(DAY - 120, 10.0, 1.0), flat(DAY - 60, 20.0, 3.0), flat(DAY, 30.0, 2.0)];
+
+        // Second candle: variance (100 + 3 * 400) / 4 - 17.5² = 18.75
+        let bands = vwap(&candles, Session::Day, Some(2.0));
+        assert_close(&bands.middle, &[Some(10.0), Some(17.5), Some(30.0)]);
+        assert_close(&bands.upper, &[Some(10.0), Some(17.5 + 2.0 * 18.75f64.sqrt()), Some(30.0)]);
+        assert_close(&bands.lower, &[Some(10.0), Some(17.5 - 2.0 * 18.75f64.sqrt()), Some(30.0)]);
+
+        let anchored = anchored_vwap(&candles, DAY - 60, None);
+        assert_close(&anchored.middle, &[None, Some(20.0), Some(24.0)]);
+        assert_close(&anchored.upper, &[None, None, None]);
+    }
+
+    #[test]
+    fn week_sessions_start_on_monday() {
+        // 1970-01-05 was a Monday
+        assert_eq!(Session::Week.start(4 * DAY), 4 * DAY);
+        assert_eq!(Session::Week.start(11 * DAY - 1), 4 * DAY);
+        assert_eq!(Session::Week.start(DAY), -3 * DAY);
+
+        let session = Session::Custom { length: DAY, offset: 48600 };
+        assert_eq!(session.start(DAY + 48599), 48600);
+    }
+
+    #[test]
+    fn custom_sessions_are_labelled_with_their_start() {
+        let session = Session::Custom { length: DAY, offset: 48600 };
+        assert_eq!(session.to_string(), "24h from 13:30");
+        assert_eq!(Session::Custom { length: 4 * 3600, offset: 3600 }.to_string(), "4h from 01:00");
+        assert_eq!(Session::Custom { length: 4 * 3600, offset: 5 * 3600 }.to_string(), "4h from 01:00");
+    }
+
+    /// Candles of a random walk, each also given as two earlier states of
+    /// the forming candle
+    fn live_candles(count: usize) -> Vec<[Candle; 3]> {
+        let mut seed: u64 = 42;
+        let mut random = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5
+        };
+
+        let mut close = 100.0;
+        (0..count)
+            .map(|i| {
+                let open = close;
+                let forming: Vec<Candle> = (0..3)
+                    .map(|_| {
+                        close = open + random() * 4.0;
+                        let high = open.max(close) + random().abs();
+                        let low = open.min(close) - random().abs();
+                        Candle::new(i as i64 * 60, open, high, low, close, 1.0)
+                    })
+                    .collect();
+                [forming[0], forming[1], forming[2]]
+            })
+            .collect()
+    }
+
+    /// Feed every state of each candle, returning the outputs for final ones
+    fn run_live<T: Incremental>(mut indicator: T, live: &[[Candle; 3]]) -> Vec<T::Output> {
+        live.iter()
+            .map(|[first, second, last]| {
+                indicator.update(first);
+                indicator.replace_last(second);
+                indicator.replace_last(last)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn incremental_matches_batch() {
+        let live = live_candles(200);
+        let candles: Vec<Candle> = live.iter().map(|states| states[2]).collect();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+        assert_close(&run_live(Ema::new(20), &live), &ema(&closes, 20));
+        assert_close(&run_live(Rsi::new(14), &live), &rsi(&closes, 14));
+        assert_close(&run_live(Atr::new(14), &live), &atr(&candles, 14));
+
+        // Sessions of 10 candles
+        let session = Session::Custom { length: 600, offset: 0 };
+        let batch = vwap(&candles, session, Some(2.0));
+        let incremental = run_live(Vwap::new(session, Some(2.0)), &live);
+        assert_close(&incremental.iter().map(|v| v[0]).collect::<Vec<_>>(), &batch.upper);
+        assert_close(&incremental.iter().map(|v| v[1]).collect::<Vec<_>>(), &batch.middle);
+
+        let batch = macd(&closes, 12, 26, 9);
+        let incremental = run_live(Macd::new(12, 26, 9), &live);
+        assert_close(&incremental.iter().map(|v| v[0]).collect::<Vec<_>>(), &batch.line);
+        assert_close(&incremental.iter().map(|v| v[1]).collect::<Vec<_>>(), &batch.signal);
+        assert_close(&incremental.iter().map(|v| v[2]).collect::<Vec<_>>(), &batch.histogram);
+    }
+
+    #[test]
+    fn window_tracker_matches_batch() {
+        let candles: Vec<Candle> = live_candles(100).iter().map(|states| states[2]).collect();
+        let compute = |candles: &[Candle]| {
+            let lines = stochastic(candles, 14, 3, 3);
+            vec![lines.k, lines.d]
+        };
+        let batch = compute(&candles);
+
+        let mut tracker = Tracker::Window(14 + 3 + 3);
+        for end in 1..=candles.len() {
+            let values = tracker.next(&candles[..end], true, compute);
+            assert_close(&values, &[batch[0][end - 1], batch[1][end - 1]]);
+        }
+    }
+
+    #[test]
+    fn replay_then_update_matches_batch() {
+        let candles: Vec<Candle> = live_candles(60).iter().map(|states| states[2]).collect();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+        let mut tracker = Tracker::Rsi(Rsi::new(14)).replay(&candles[..59]);
+        let values = tracker.next(&candles, true, |_| Vec::new());
+        assert_close(&values, &rsi(&closes, 14)[59..]);
+    }
+}
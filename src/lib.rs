@@ -0,0 +1,26 @@
+//! A candlestick chart widget for iced, with pluggable market data sources.
+//!
+//! [`CandlestickChart`] renders a series of [`Candle`]s on a canvas, handles
+//! zoom and pan itself and reports to the host through [`ChartMessage`]. Data
+//! can be loaded from any [`DataSource`], such as [`BinanceSource`] or the
+//! offline [`FixtureSource`].
+pub mod axis;
+pub mod binance;
+pub mod candlestick;
+pub mod indicator;
+pub mod pane;
+pub mod source;
+pub mod timezone;
+pub mod trades;
+pub mod transform;
+pub mod viewport;
+
+pub use binance::BinanceSource;
+pub use candlestick::{Candle, CandlestickChart, ChartMessage, ChartStyle, ChartType, VolumeUnit};
+pub use indicator::{Incremental, Overlay, Session};
+pub use pane::{PaneId, PaneKind};
+pub use source::{DataSource, FixtureSource, Interval, SymbolInfo};
+pub use timezone::DisplayTimezone;
+pub use trades::{BarKind, BarUpdate, Trade, TradeAggregator};
+pub use transform::BoxSize;
+pub use viewport::{PriceRange, ScaleMode, Viewport};
@@ -0,0 +1,235 @@
+use crate::candlestick::Candle;
+use crate::indicator::{self, Atr, Macd, Rsi, Tracker};
+use iced::{Point, Rectangle, Size};
+use std::fmt;
+
+/// Space between panes, dragged to resize them
+pub(crate) const PANE_GAP: f32 = 6.0;
+
+/// Share of the chart height a new pane starts with
+pub(crate) const DEFAULT_PANE_RATIO: f32 = 0.2;
+
+// Smallest share of the chart height a pane can be resized to
+const MIN_PANE_RATIO: f32 = 0.05;
+
+// Share of the chart height always left to the price pane
+const MIN_PRICE_RATIO: f32 = 0.2;
+
+// Side of the move and close buttons in the corner of a pane
+const BUTTON_SIZE: f32 = 14.0;
+
+/// Identifies a pane, stays the same when panes are moved or closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PaneId(pub(crate) usize);
+
+/// What a pane below the prices shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneKind {
+    Volume,
+    /// Relative strength index over a period
+    Rsi(usize),
+    /// MACD line, signal line and histogram, see [`indicator::macd`]
+    Macd { fast: usize, slow: usize, signal: usize },
+    /// %K and %D, see [`indicator::stochastic`]
+    Stochastic { period: usize, smooth: usize, signal: usize },
+    /// Average true range over a period
+    Atr(usize),
+}
+
+impl PaneKind {
+    /// Lines of the indicator, each with a value or `None` for every candle
+    ///
+    /// Volume is drawn straight from the candles, so it has none.
+    pub fn compute(self, candles: &[Candle]) -> Vec<Vec<Option<f64>>> {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        match self {
+            PaneKind::Volume => Vec::new(),
+            PaneKind::Rsi(period) => vec![indicator::rsi(&closes, period)],
+            PaneKind::Macd { fast, slow, signal } => {
+                let macd = indicator::macd(&closes, fast, slow, signal);
+                vec![macd.line, macd.signal, macd.histogram]
+            }
+            PaneKind::Stochastic { period, smooth, signal } => {
+                let stochastic = indicator::stochastic(candles, period, smooth, signal);
+                vec![stochastic.k, stochastic.d]
+            }
+            PaneKind::Atr(period) => vec![indicator::atr(candles, period)],
+        }
+    }
+
+    /// Running state for live updates, through `candles`
+    pub(crate) fn tracker(self, candles: &[Candle]) -> Tracker {
+        match self {
+            PaneKind::Volume => Tracker::Window(0),
+            PaneKind::Rsi(period) => Tracker::Rsi(Rsi::new(period)).replay(candles),
+            PaneKind::Macd { fast, slow, signal } => Tracker::Macd(Macd::new(fast, slow, signal)).replay(candles),
+            PaneKind::Stochastic { period, smooth, signal } => Tracker::Window(period + smooth + signal),
+            PaneKind::Atr(period) => Tracker::Atr(Atr::new(period)).replay(candles),
+        }
+    }
+
+    /// Oversold and overbought levels of oscillators bound from 0 to 100
+    pub fn thresholds(self) -> Option<(f64, f64)> {
+        match self {
+            PaneKind::Rsi(_) => Some((30.0, 70.0)),
+            PaneKind::Stochastic { .. } => Some((20.0, 80.0)),
+            PaneKind::Volume | PaneKind::Macd { .. } | PaneKind::Atr(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PaneKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaneKind::Volume => write!(f, "Volume"),
+            PaneKind::Rsi(period) => write!(f, "RSI {}", period),
+            PaneKind::Macd { fast, slow, signal } => write!(f, "MACD {} {} {}", fast, slow, signal),
+            PaneKind::Stochastic { period, smooth, signal } => write!(f, "Stoch {} {} {}", period, smooth, signal),
+            PaneKind::Atr(period) => write!(f, "ATR {}", period),
+        }
+    }
+}
+
+/// A pane stacked below the price pane
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pane {
+    pub id: PaneId,
+    pub kind: PaneKind,
+}
+
+/// Buttons in the top right corner of a pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PaneButton {
+    Up,
+    Down,
+    Close,
+}
+
+impl PaneButton {
+    pub const ALL: [PaneButton; 3] = [PaneButton::Up, PaneButton::Down, PaneButton::Close];
+
+    /// Where the button sits in `pane`
+    pub fn bounds(self, pane: Rectangle) -> Rectangle {
+        let slot = match self {
+            PaneButton::Up => 3.0,
+            PaneButton::Down => 2.0,
+            PaneButton::Close => 1.0,
+        };
+        Rectangle::new(
+            Point::new(pane.x + pane.width - slot * (BUTTON_SIZE + 4.0), pane.y + 4.0),
+            Size::new(BUTTON_SIZE, BUTTON_SIZE),
+        )
+    }
+}
+
+/// Where the panes of a chart go
+#[derive(Debug, Clone)]
+pub(crate) struct Layout {
+    pub price: Rectangle,
+    pub panes: Vec<(Pane, Rectangle)>, // Top to bottom
+    height: f32,                       // Of all panes and the gaps between them
+}
+
+impl Layout {
+    /// Stack `panes` below the price pane in `area`, each taking its `ratio`
+    /// of the height
+    pub fn new(area: Rectangle, panes: &[Pane], ratio: impl Fn(PaneId) -> f32) -> Self {
+        let ratios: Vec<f32> = panes.iter().map(|pane| ratio(pane.id)).collect();
+
+        // Squeeze the panes rather than the prices when there are many of them
+        let total: f32 = ratios.iter().sum();
+        let squeeze = if total > 1.0 - MIN_PRICE_RATIO { (1.0 - MIN_PRICE_RATIO) / total } else { 1.0 };
+        let gaps = PANE_GAP * panes.len() as f32;
+        let heights: Vec<f32> = ratios
+            .iter()
+            .map(|ratio| (ratio * squeeze * area.height).max(1.0))
+            .collect();
+
+        let price_height = (area.height - gaps - heights.iter().sum::<f32>()).max(1.0);
+        let price = Rectangle::new(area.position(), Size::new(area.width, price_height));
+
+        let mut y = area.y + price_height;
+        let panes = panes
+            .iter()
+            .zip(heights)
+            .map(|(pane, height)| {
+                let bounds = Rectangle::new(Point::new(area.x, y + PANE_GAP), Size::new(area.width, height));
+                y += PANE_GAP + height;
+                (*pane, bounds)
+            })
+            .collect();
+
+        Self { price, panes, height: area.height }
+    }
+
+    /// Every pane, the price pane first
+    pub fn all(&self) -> impl Iterator<Item = Rectangle> + '_ {
+        std::iter::once(self.price).chain(self.panes.iter().map(|(_, bounds)| *bounds))
+    }
+
+    /// Bottom edge of the lowest pane, where the time axis starts
+    pub fn bottom(&self) -> f32 {
+        let last = self.all().last().unwrap_or(self.price);
+        last.y + last.height
+    }
+
+    /// Pane below the prices at `position`
+    pub fn pane_at(&self, position: Point) -> Option<(Pane, Rectangle)> {
+        self.panes.iter().copied().find(|(_, bounds)| bounds.contains(position))
+    }
+
+    /// Index of the pane whose top divider is at `position`
+    pub fn divider_at(&self, position: Point) -> Option<usize> {
+        self.panes.iter().position(|(_, bounds)| {
+            position.x >= bounds.x
+                && position.x <= bounds.x + bounds.width
+                && position.y >= bounds.y - PANE_GAP
+                && position.y < bounds.y
+        })
+    }
+
+    /// Corner button of a pane at `position`
+    pub fn button_at(&self, position: Point) -> Option<(PaneId, PaneButton)> {
+        let (pane, bounds) = self.pane_at(position)?;
+        PaneButton::ALL
+            .into_iter()
+            .find(|button| button.bounds(bounds).contains(position))
+            .map(|button| (pane.id, button))
+    }
+
+    /// New height ratios of the panes once divider `index` is dragged to `y`
+    ///
+    /// Every pane gets the ratio of its height as laid out, so panes that `new`
+    /// squeezed keep their size. The price pane takes what is left, so it never
+    /// appears in the result.
+    pub fn resize(&self, index: usize, y: f32) -> Vec<(PaneId, f32)> {
+        let total_height = self.height;
+        let Some((lower, lower_bounds)) = self.panes.get(index).copied() else {
+            return Vec::new();
+        };
+        let (upper, upper_bounds) = match index.checked_sub(1) {
+            Some(above) => (Some(self.panes[above].0), self.panes[above].1),
+            None => (None, self.price),
+        };
+
+        let upper_min = if upper.is_some() { MIN_PANE_RATIO } else { MIN_PRICE_RATIO } * total_height;
+        let lower_min = MIN_PANE_RATIO * total_height;
+        let bottom = lower_bounds.y + lower_bounds.height;
+        let max_y = bottom - lower_min - PANE_GAP;
+        let divider = (y - PANE_GAP / 2.0).min(max_y).max(upper_bounds.y + upper_min);
+
+        self.panes
+            .iter()
+            .map(|(pane, bounds)| {
+                let height = if pane.id == lower.id {
+                    bottom - divider - PANE_GAP
+                } else if Some(pane.id) == upper.map(|upper| upper.id) {
+                    divider - upper_bounds.y
+                } else {
+                    bounds.height
+                };
+                (pane.id, height / total_height)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,266 @@
+use crate::candlestick::Candle;
+use crate::trades::Trade;
+use iced::futures::future::BoxFuture;
+use iced::Subscription;
+use std::fmt;
+
+/// Candle timeframe/interval options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Interval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    #[default]
+    OneHour,
+    FourHours,
+    OneDay,
+}
+
+impl Interval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::FifteenMinutes => "15m",
+            Interval::ThirtyMinutes => "30m",
+            Interval::OneHour => "1h",
+            Interval::FourHours => "4h",
+            Interval::OneDay => "1d",
+        }
+    }
+
+    pub fn to_minutes(self) -> i64 {
+        match self {
+            Interval::OneMinute => 1,
+            Interval::FiveMinutes => 5,
+            Interval::FifteenMinutes => 15,
+            Interval::ThirtyMinutes => 30,
+            Interval::OneHour => 60,
+            Interval::FourHours => 240,
+            Interval::OneDay => 1440,
+        }
+    }
+
+    pub fn all() -> Vec<Interval> {
+        vec![
+            Interval::OneMinute,
+            Interval::FiveMinutes,
+            Interval::FifteenMinutes,
+            Interval::ThirtyMinutes,
+            Interval::OneHour,
+            Interval::FourHours,
+            Interval::OneDay,
+        ]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1 Minute",
+            Interval::FiveMinutes => "5 Minutes",
+            Interval::FifteenMinutes => "15 Minutes",
+            Interval::ThirtyMinutes => "30 Minutes",
+            Interval::OneHour => "1 Hour",
+            Interval::FourHours => "4 Hours",
+            Interval::OneDay => "1 Day",
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+/// A tradable symbol and how its prices are quoted
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolInfo {
+    pub name: String,
+    /// Decimals in the price tick size, e.g. 2 for a 0.01 tick
+    pub price_precision: usize,
+}
+
+impl SymbolInfo {
+    pub fn new(name: impl Into<String>, price_precision: usize) -> Self {
+        Self {
+            name: name.into(),
+            price_precision,
+        }
+    }
+}
+
+impl fmt::Display for SymbolInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A provider of market data for the chart
+///
+/// Futures are boxed and `'static` so they can be handed straight to `Task::perform`.
+pub trait DataSource {
+    /// Name of the source, e.g. "Binance"
+    fn name(&self) -> &str;
+
+    /// List the symbols this source can serve
+    fn symbols(&self) -> BoxFuture<'static, Result<Vec<SymbolInfo>, String>>;
+
+    /// Fetch the most recent `limit` candles for `symbol`, oldest first
+    fn klines(&self, symbol: &str, interval: Interval, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>>;
+
+    /// Fetch up to `limit` candles opening before `end_time` (seconds), oldest first
+    ///
+    /// An empty result means there is no older history.
+    fn klines_before(&self, symbol: &str, interval: Interval, end_time: i64, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>>;
+
+    /// Live updates of the forming candle, if the source can stream them
+    fn live(&self, _symbol: &str, _interval: Interval) -> Option<Subscription<Result<Candle, String>>> {
+        None
+    }
+
+    /// Fetch the most recent `limit` trades for `symbol`, oldest first
+    fn trades(&self, _symbol: &str, _limit: u32) -> BoxFuture<'static, Result<Vec<Trade>, String>> {
+        let error = format!("{} does not provide trades", self.name());
+        Box::pin(async move { Err(error) })
+    }
+
+    /// Live trades, if the source can stream them
+    fn live_trades(&self, _symbol: &str) -> Option<Subscription<Result<Trade, String>>> {
+        None
+    }
+}
+
+/// In-memory data source, for running offline and for tests
+///
+/// Series are served as given, whatever interval is requested, unless candles
+/// were added for that interval with [`FixtureSource::with_interval`].
+#[derive(Debug, Clone, Default)]
+pub struct FixtureSource {
+    series: Vec<(SymbolInfo, Vec<Candle>)>,
+    intervals: Vec<(String, Interval, Vec<Candle>)>, // Served in place of `series` at their interval
+    trades: Vec<(String, Vec<Trade>)>,
+}
+
+impl FixtureSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add (or replace) the candles served for `symbol`
+    pub fn with_symbol(mut self, symbol: SymbolInfo, candles: Vec<Candle>) -> Self {
+        self.series.retain(|(s, _)| s.name != symbol.name);
+        self.series.push((symbol, candles));
+        self
+    }
+
+    /// Add (or replace) the candles served for `symbol` when `interval` is requested
+    pub fn with_interval(mut self, symbol: &str, interval: Interval, candles: Vec<Candle>) -> Self {
+        self.intervals.retain(|(s, i, _)| s != symbol || *i != interval);
+        self.intervals.push((symbol.to_string(), interval, candles));
+        self
+    }
+
+    /// Add (or replace) the trades served for `symbol`, e.g. from [`crate::trades::read_trades`]
+    pub fn with_trades(mut self, symbol: &str, trades: Vec<Trade>) -> Self {
+        self.trades.retain(|(s, _)| s != symbol);
+        self.trades.push((symbol.to_string(), trades));
+        self
+    }
+
+    /// Deterministic random-walk candles for each `(symbol, start price)`, `count`
+    /// of them at every interval
+    pub fn random_walk(symbols: &[(&str, f64)], count: usize) -> Self {
+        symbols
+            .iter()
+            .enumerate()
+            .fold(Self::new(), |source, (i, (symbol, start_price))| {
+                // Enough decimals for about five significant digits
+                let precision = (4 - start_price.log10().floor() as i32).clamp(2, 8) as usize;
+                let seed = i as u64 + 1;
+                let source = source.with_symbol(
+                    SymbolInfo::new(*symbol, precision),
+                    random_walk(seed, *start_price, Interval::default(), count),
+                );
+                Interval::all().into_iter().fold(source, |source, interval| {
+                    source.with_interval(symbol, interval, random_walk(seed, *start_price, interval, count))
+                })
+            })
+    }
+
+    fn candles(&self, symbol: &str, interval: Interval) -> Result<&[Candle], String> {
+        if let Some((_, _, candles)) = self.intervals.iter().find(|(s, i, _)| s == symbol && *i == interval) {
+            return Ok(candles);
+        }
+
+        self.series
+            .iter()
+            .find(|(s, _)| s.name == symbol)
+            .map(|(_, candles)| candles.as_slice())
+            .ok_or_else(|| format!("Unknown symbol: {}", symbol))
+    }
+}
+
+impl DataSource for FixtureSource {
+    fn name(&self) -> &str {
+        "Fixture"
+    }
+
+    fn symbols(&self) -> BoxFuture<'static, Result<Vec<SymbolInfo>, String>> {
+        let symbols = self.series.iter().map(|(s, _)| s.clone()).collect();
+        Box::pin(async move { Ok(symbols) })
+    }
+
+    fn klines(&self, symbol: &str, interval: Interval, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>> {
+        let result = self
+            .candles(symbol, interval)
+            .map(|candles| candles[candles.len().saturating_sub(limit as usize)..].to_vec());
+        Box::pin(async move { result })
+    }
+
+    fn klines_before(&self, symbol: &str, interval: Interval, end_time: i64, limit: u32) -> BoxFuture<'static, Result<Vec<Candle>, String>> {
+        let result = self.candles(symbol, interval).map(|candles| {
+            let end = candles.partition_point(|c| c.timestamp < end_time);
+            candles[end.saturating_sub(limit as usize)..end].to_vec()
+        });
+        Box::pin(async move { result })
+    }
+
+    fn trades(&self, symbol: &str, limit: u32) -> BoxFuture<'static, Result<Vec<Trade>, String>> {
+        let result = self
+            .trades
+            .iter()
+            .find(|(s, _)| s == symbol)
+            .map(|(_, trades)| trades[trades.len().saturating_sub(limit as usize)..].to_vec())
+            .ok_or_else(|| format!("No trades for {}", symbol));
+        Box::pin(async move { result })
+    }
+}
+
+/// Generate `count` candles ending at the current interval boundary
+fn random_walk(seed: u64, start_price: f64, interval: Interval, count: usize) -> Vec<Candle> {
+    // xorshift, good enough for fake prices and stable across runs
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    let step = interval.to_minutes() * 60;
+    let now = chrono::Utc::now().timestamp();
+    let first = now - now.rem_euclid(step) - step * (count as i64 - 1);
+
+    let mut close = start_price;
+    (0..count as i64)
+        .map(|i| {
+            let open = close;
+            close = open * (1.0 + (next() - 0.5) * 0.02);
+            let high = open.max(close) * (1.0 + next() * 0.005);
+            let low = open.min(close) * (1.0 - next() * 0.005);
+            let volume = 100.0 + next() * 900.0;
+            Candle::new(first + i * step, open, high, low, close, volume)
+        })
+        .collect()
+}
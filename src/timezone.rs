@@ -0,0 +1,70 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// Timezone used to display timestamps on the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DisplayTimezone {
+    #[default]
+    Utc,
+    /// The timezone of the machine running the app
+    Local,
+    /// An IANA zone such as `America/New_York`
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    /// Look up an IANA zone by name, e.g. "Asia/Singapore"
+    pub fn named(name: &str) -> Option<Self> {
+        name.parse().ok().map(DisplayTimezone::Named)
+    }
+
+    /// A selection of common zones for pickers
+    pub fn presets() -> Vec<DisplayTimezone> {
+        vec![
+            DisplayTimezone::Utc,
+            DisplayTimezone::Local,
+            DisplayTimezone::Named(Tz::America__New_York),
+            DisplayTimezone::Named(Tz::Europe__London),
+            DisplayTimezone::Named(Tz::Asia__Singapore),
+            DisplayTimezone::Named(Tz::Asia__Tokyo),
+        ]
+    }
+
+    /// Wall-clock time of a timestamp (seconds) in this zone
+    pub fn local_time(self, timestamp: i64) -> Option<NaiveDateTime> {
+        let utc = DateTime::from_timestamp(timestamp, 0)?;
+        Some(match self {
+            DisplayTimezone::Utc => utc.naive_utc(),
+            DisplayTimezone::Local => utc.with_timezone(&Local).naive_local(),
+            DisplayTimezone::Named(tz) => utc.with_timezone(&tz).naive_local(),
+        })
+    }
+
+    /// Timestamp (seconds) of a wall-clock time in this zone, the earlier one
+    /// when a DST change makes it ambiguous and `None` when it is skipped
+    pub fn timestamp(self, time: NaiveDateTime) -> Option<i64> {
+        match self {
+            DisplayTimezone::Utc => Some(time.and_utc().timestamp()),
+            DisplayTimezone::Local => Local.from_local_datetime(&time).earliest().map(|t| t.timestamp()),
+            DisplayTimezone::Named(tz) => tz.from_local_datetime(&time).earliest().map(|t| t.timestamp()),
+        }
+    }
+
+    /// Format a timestamp (seconds) with a `strftime` pattern
+    pub fn format(self, timestamp: i64, pattern: &str) -> String {
+        self.local_time(timestamp)
+            .map(|time| time.format(pattern).to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayTimezone::Utc => write!(f, "UTC"),
+            DisplayTimezone::Local => write!(f, "Local"),
+            DisplayTimezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
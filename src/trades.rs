@@ -0,0 +1,254 @@
+use crate::candlestick::Candle;
+use std::fmt;
+use std::path::Path;
+
+/// A single trade, or trades aggregated at one price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub id: u64, // Increases with every trade of a symbol
+    pub timestamp_ms: i64,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+impl Trade {
+    pub fn new(id: u64, timestamp_ms: i64, price: f64, quantity: f64) -> Self {
+        Self {
+            id,
+            timestamp_ms,
+            price,
+            quantity,
+        }
+    }
+}
+
+/// When a bar built from trades closes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarKind {
+    /// Once its high and low are this far apart
+    Range(f64),
+    /// Once this much has traded in it
+    Volume(f64),
+}
+
+impl fmt::Display for BarKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BarKind::Range(size) => write!(f, "Range {}", size),
+            BarKind::Volume(size) => write!(f, "Volume {}", size),
+        }
+    }
+}
+
+/// Change to the bar series after a trade
+#[derive(Debug, Clone, Copy)]
+pub enum BarUpdate {
+    /// The trade opened a new bar
+    Opened(Candle),
+    /// The trade went into the last bar, which may now be complete
+    Updated(Candle),
+}
+
+/// Builds range or volume bars from a stream of trades
+///
+/// Bars are [`Candle`]s opening at their first trade, so their timestamps are
+/// irregular and several bars may share the same second.
+#[derive(Debug, Clone)]
+pub struct TradeAggregator {
+    kind: BarKind,
+    forming: Option<Candle>,
+}
+
+impl TradeAggregator {
+    pub fn new(kind: BarKind) -> Self {
+        Self { kind, forming: None }
+    }
+
+    /// Bars of `trades` (oldest first), the last one possibly still forming
+    ///
+    /// Continues the forming bar, whose update comes first if there is one.
+    pub fn aggregate(&mut self, trades: &[Trade]) -> Vec<Candle> {
+        let mut bars: Vec<Candle> = self.forming.into_iter().collect();
+
+        for trade in trades {
+            match self.push(trade) {
+                BarUpdate::Opened(bar) => bars.push(bar),
+                BarUpdate::Updated(bar) => {
+                    if let Some(last) = bars.last_mut() {
+                        *last = bar;
+                    }
+                }
+            }
+        }
+
+        bars
+    }
+
+    /// Add a trade to the forming bar, or open a new one
+    pub fn push(&mut self, trade: &Trade) -> BarUpdate {
+        let Some(mut bar) = self.forming else {
+            let bar = Candle::new(
+                trade.timestamp_ms / 1000,
+                trade.price,
+                trade.price,
+                trade.price,
+                trade.price,
+                trade.quantity,
+            )
+            .with_quote_volume(trade.price * trade.quantity);
+            self.forming = (!self.is_complete(&bar)).then_some(bar);
+            return BarUpdate::Opened(bar);
+        };
+
+        bar.high = bar.high.max(trade.price);
+        bar.low = bar.low.min(trade.price);
+        bar.close = trade.price;
+        bar.volume += trade.quantity;
+        bar.quote_volume += trade.price * trade.quantity;
+
+        // The next trade opens a new bar
+        self.forming = (!self.is_complete(&bar)).then_some(bar);
+        BarUpdate::Updated(bar)
+    }
+
+    fn is_complete(&self, bar: &Candle) -> bool {
+        match self.kind {
+            BarKind::Range(size) => bar.high - bar.low >= size,
+            BarKind::Volume(size) => bar.volume >= size,
+        }
+    }
+}
+
+/// Read trades recorded as `timestamp_ms,price,quantity` lines, oldest first
+///
+/// Blank lines and lines starting with `#` are skipped, as is a header line.
+/// Trades are numbered in the order of the file.
+pub fn read_trades(path: impl AsRef<Path>) -> Result<Vec<Trade>, String> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let mut trades = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let parsed = match fields.as_slice() {
+            [timestamp, price, quantity] => timestamp
+                .parse()
+                .ok()
+                .zip(price.parse().ok())
+                .zip(quantity.parse().ok())
+                .map(|((timestamp, price), quantity)| Trade::new(trades.len() as u64, timestamp, price, quantity)),
+            _ => None,
+        };
+
+        match parsed {
+            Some(trade) => trades.push(trade),
+            None if number == 0 => continue, // Header
+            None => return Err(format!("{}:{}: expected timestamp_ms,price,quantity", path.display(), number + 1)),
+        }
+    }
+
+    Ok(trades)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: u64, price: f64, quantity: f64) -> Trade {
+        Trade::new(id, 1_700_000_000_000 + id as i64 * 250, price, quantity)
+    }
+
+    fn ohlcv(bar: &Candle) -> [f64; 5] {
+        [bar.open, bar.high, bar.low, bar.close, bar.volume]
+    }
+
+    #[test]
+    fn range_bar_closes_on_its_span() {
+        let mut aggregator = TradeAggregator::new(BarKind::Range(1.0));
+        let bars = aggregator.aggregate(&[
+            trade(0, 100.0, 1.0),
+            trade(1, 100.5, 1.0),
+            trade(2, 99.75, 1.0),
+            // Span of exactly 1.0 closes the bar
+            trade(3, 100.75, 2.0),
+            trade(4, 101.0, 1.0),
+        ]);
+
+        assert_eq!(bars.len(), 2);
+        assert_eq!(ohlcv(&bars[0]), [100.0, 100.75, 99.75, 100.75, 5.0]);
+        assert_eq!(ohlcv(&bars[1]), [101.0, 101.0, 101.0, 101.0, 1.0]);
+        assert_eq!(bars[1].timestamp, 1_700_000_001);
+    }
+
+    #[test]
+    fn volume_bar_closes_on_its_threshold() {
+        let mut aggregator = TradeAggregator::new(BarKind::Volume(3.0));
+        let bars = aggregator.aggregate(&[
+            trade(0, 10.0, 1.0),
+            // Reaches exactly 3.0
+            trade(1, 11.0, 2.0),
+            // Larger than a whole bar on its own
+            trade(2, 12.0, 5.0),
+            trade(3, 13.0, 0.5),
+        ]);
+
+        assert_eq!(bars.len(), 3);
+        assert_eq!(ohlcv(&bars[0]), [10.0, 11.0, 10.0, 11.0, 3.0]);
+        assert_eq!(ohlcv(&bars[1]), [12.0, 12.0, 12.0, 12.0, 5.0]);
+        assert_eq!(ohlcv(&bars[2]), [13.0, 13.0, 13.0, 13.0, 0.5]);
+    }
+
+    #[test]
+    fn push_opens_then_updates() {
+        let mut aggregator = TradeAggregator::new(BarKind::Volume(2.0));
+
+        assert!(matches!(aggregator.push(&trade(0, 10.0, 1.0)), BarUpdate::Opened(bar) if bar.volume == 1.0));
+        assert!(matches!(aggregator.push(&trade(1, 11.0, 1.0)), BarUpdate::Updated(bar) if bar.volume == 2.0));
+        // The last bar is complete, so the next trade opens another
+        assert!(matches!(aggregator.push(&trade(2, 12.0, 1.0)), BarUpdate::Opened(bar) if bar.open == 12.0));
+    }
+
+    #[test]
+    fn aggregate_continues_the_forming_bar() {
+        let mut aggregator = TradeAggregator::new(BarKind::Volume(3.0));
+        let first = aggregator.aggregate(&[trade(0, 10.0, 1.0)]);
+        assert_eq!(first.len(), 1);
+
+        let more = aggregator.aggregate(&[trade(1, 9.0, 1.0), trade(2, 12.0, 1.0), trade(3, 11.0, 1.0)]);
+        assert_eq!(more.len(), 2);
+        assert_eq!(more[0].timestamp, first[0].timestamp);
+        assert_eq!(ohlcv(&more[0]), [10.0, 12.0, 9.0, 12.0, 3.0]);
+        assert_eq!(ohlcv(&more[1]), [11.0, 11.0, 11.0, 11.0, 1.0]);
+    }
+
+    fn read(name: &str, content: &str) -> Result<Vec<Trade>, String> {
+        let path = std::env::temp_dir().join(format!("iced_candlestick_{}_{}.csv", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        let result = read_trades(&path);
+        std::fs::remove_file(&path).unwrap();
+        result
+    }
+
+    #[test]
+    fn read_trades_skips_header_comments_and_blanks() {
+        let trades = read(
+            "valid",
+            "timestamp_ms,price,quantity\n# recorded offline\n1000,10.5,0.25\n\n 2000 , 11 , 1 \n",
+        )
+        .unwrap();
+
+        assert_eq!(trades, [Trade::new(0, 1000, 10.5, 0.25), Trade::new(1, 2000, 11.0, 1.0)]);
+    }
+
+    #[test]
+    fn read_trades_reports_the_bad_line() {
+        let error = read("invalid", "1000,10.5,0.25\n2000,oops,1\n").unwrap_err();
+        assert!(error.ends_with(":2: expected timestamp_ms,price,quantity"), "{}", error);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::candlestick::Candle;
+use crate::indicator;
+use std::fmt;
+
+/// Heikin-Ashi candles of `candles`, with the same timestamps and volumes
+///
+/// Each close averages the candle's prices and each open sits halfway through
+/// the previous Heikin-Ashi body, which smooths out noise along a trend.
+pub fn heikin_ashi(candles: &[Candle]) -> Vec<Candle> {
+    let mut result: Vec<Candle> = Vec::with_capacity(candles.len());
+
+    for candle in candles {
+        let close = (candle.open + candle.high + candle.low + candle.close) / 4.0;
+        let open = match result.last() {
+            Some(previous) => (previous.open + previous.close) / 2.0,
+            None => (candle.open + candle.close) / 2.0,
+        };
+
+        result.push(Candle::new(
+            candle.timestamp,
+            open,
+            candle.high.max(open).max(close),
+            candle.low.min(open).min(close),
+            close,
+            candle.volume,
+        )
+        .with_quote_volume(candle.quote_volume));
+    }
+
+    result
+}
+
+/// Size of a Renko brick or Point-and-Figure box, also the unit of Kagi reversals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoxSize {
+    /// A fixed price amount
+    Fixed(f64),
+    /// The average true range over this many candles, as of the last candle
+    /// when the series was loaded
+    Atr(usize),
+}
+
+impl BoxSize {
+    /// Price amount of a box for `candles`, `None` without enough candles
+    pub fn resolve(self, candles: &[Candle]) -> Option<f64> {
+        let size = match self {
+            BoxSize::Fixed(size) => size,
+            BoxSize::Atr(period) => atr(candles, period)?,
+        };
+        (size > 0.0 && size.is_finite()).then_some(size)
+    }
+}
+
+impl Default for BoxSize {
+    fn default() -> Self {
+        BoxSize::Atr(14)
+    }
+}
+
+impl fmt::Display for BoxSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoxSize::Fixed(size) => write!(f, "Box {}", size),
+            BoxSize::Atr(period) => write!(f, "ATR {}", period),
+        }
+    }
+}
+
+/// A brick, line or column of a chart that is not time-based, drawn like a
+/// candle whose timestamp is the first candle it covers and `end` the last
+#[derive(Debug, Clone, Copy)]
+pub struct Bar {
+    pub candle: Candle,
+    pub end: i64,
+}
+
+/// Base and quote volume of the candles a bar covers
+#[derive(Debug, Clone, Copy, Default)]
+struct Volume {
+    base: f64,
+    quote: f64,
+}
+
+impl Volume {
+    fn add(&mut self, candle: &Candle) {
+        self.base += candle.volume;
+        self.quote += candle.quote_volume;
+    }
+
+    /// Candle carrying this volume
+    fn candle(self, timestamp: i64, open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle::new(timestamp, open, high, low, close, self.base).with_quote_volume(self.quote)
+    }
+}
+
+/// Average true range of the last `period` candles, smoothed the Wilder way
+pub fn atr(candles: &[Candle], period: usize) -> Option<f64> {
+    indicator::atr(candles, period).last().copied().flatten()
+}
+
+/// Renko bricks of `box_size` from the closes of `candles`
+///
+/// A brick is added whenever the close moves a box past the last brick, and
+/// `reversal` boxes past its open to turn around (1 is classic Renko). Bricks
+/// opened by the same candle share its time, the first one carries the volume.
+pub fn renko(candles: &[Candle], box_size: f64, reversal: usize) -> Vec<Bar> {
+    let mut bars = Vec::new();
+    let Some(first) = candles.first() else {
+        return bars;
+    };
+
+    let reversal = reversal.max(1) as f64;
+    // Edges of the last brick, a zero-height one on the box grid to start with
+    let mut top = (first.close / box_size).round() * box_size;
+    let mut bottom = top;
+    let mut rising: Option<bool> = None;
+    let mut start = None;
+    let mut volume = Volume::default();
+
+    for candle in candles {
+        let start_time = *start.get_or_insert(candle.timestamp);
+        volume.add(candle);
+
+        let up = ((candle.close - top) / box_size + 1e-9).floor();
+        let down = ((bottom - candle.close) / box_size + 1e-9).floor();
+        let (count, from, step) = if up >= if rising == Some(false) { reversal } else { 1.0 } {
+            rising = Some(true);
+            (up, top, box_size)
+        } else if down >= if rising == Some(true) { reversal } else { 1.0 } {
+            rising = Some(false);
+            (down, bottom, -box_size)
+        } else {
+            continue;
+        };
+
+        for i in 0..count as usize {
+            let open = from + step * i as f64;
+            let close = open + step;
+            bars.push(Bar {
+                candle: volume.candle(start_time, open, open.max(close), open.min(close), close),
+                end: candle.timestamp,
+            });
+            volume = Volume::default();
+        }
+
+        let last = from + step * count;
+        (top, bottom) = if step > 0.0 { (last, last - step) } else { (last - step, last) };
+        start = None;
+    }
+
+    bars
+}
+
+/// Kagi lines from the closes of `candles`, turning when the close retraces
+/// `reversal_amount` from the last extreme
+///
+/// Each line is a bar from where it turned to its extreme; the last one is
+/// still forming.
+pub fn kagi(candles: &[Candle], reversal_amount: f64) -> Vec<Bar> {
+    let mut bars = Vec::new();
+    let Some(first) = candles.first() else {
+        return bars;
+    };
+
+    let mut open = first.close;
+    let mut extreme = first.close;
+    let mut rising: Option<bool> = None;
+    let mut start = first.timestamp;
+    let mut end = first.timestamp;
+    let mut volume = Volume::default();
+
+    let line = |open: f64, close: f64, start: i64, end: i64, volume: Volume| Bar {
+        candle: volume.candle(start, open, open.max(close), open.min(close), close),
+        end,
+    };
+
+    for candle in candles {
+        let price = candle.close;
+        let turned = match rising {
+            None => {
+                if (price - open).abs() >= reversal_amount {
+                    rising = Some(price > open);
+                    extreme = price;
+                }
+                false
+            }
+            Some(true) if price > extreme => {
+                extreme = price;
+                false
+            }
+            Some(false) if price < extreme => {
+                extreme = price;
+                false
+            }
+            Some(up) => {
+                let retrace = if up { extreme - price } else { price - extreme };
+                retrace >= reversal_amount
+            }
+        };
+
+        if turned {
+            bars.push(line(open, extreme, start, end, volume));
+            open = extreme;
+            extreme = price;
+            rising = rising.map(|up| !up);
+            start = candle.timestamp;
+            volume = Volume::default();
+        }
+
+        end = candle.timestamp;
+        volume.add(candle);
+    }
+
+    if rising.is_some() {
+        bars.push(line(open, extreme, start, end, volume));
+    }
+
+    bars
+}
+
+/// Point-and-Figure columns of `box_size` from the closes of `candles`
+///
+/// Rising columns of X and falling columns of O, a new column starting once
+/// the close fills `reversal` boxes the other way (3 is the classic). Bars go
+/// from the column's first box edge to its last, so each spans whole boxes.
+pub fn point_and_figure(candles: &[Candle], box_size: f64, reversal: usize) -> Vec<Bar> {
+    let mut bars = Vec::new();
+    let Some(first) = candles.first() else {
+        return bars;
+    };
+
+    let reversal = reversal.max(1) as f64 * box_size - box_size * 1e-9;
+    // Box edges the column spans
+    let anchor = (first.close / box_size).round() * box_size;
+    let mut top = anchor;
+    let mut bottom = anchor;
+    let mut rising: Option<bool> = None;
+    let mut start = first.timestamp;
+    let mut end = first.timestamp;
+    let mut volume = Volume::default();
+
+    let column = |bottom: f64, top: f64, up: bool, start: i64, end: i64, volume: Volume| {
+        let (open, close) = if up { (bottom, top) } else { (top, bottom) };
+        Bar {
+            candle: volume.candle(start, open, top, bottom, close),
+            end,
+        }
+    };
+
+    for candle in candles {
+        let box_below = (candle.close / box_size + 1e-9).floor() * box_size;
+        let box_above = (candle.close / box_size - 1e-9).ceil() * box_size;
+
+        match rising {
+            None => {
+                if box_below >= anchor + box_size {
+                    rising = Some(true);
+                    top = box_below;
+                } else if box_above <= anchor - box_size {
+                    rising = Some(false);
+                    bottom = box_above;
+                }
+            }
+            Some(true) if box_below > top => top = box_below,
+            Some(false) if box_above < bottom => bottom = box_above,
+            // The new column starts a box away from the last one's extreme
+            Some(true) if top - box_size - box_above >= reversal => {
+                bars.push(column(bottom, top, true, start, end, volume));
+                top -= box_size;
+                bottom = box_above;
+                rising = Some(false);
+                start = candle.timestamp;
+                volume = Volume::default();
+            }
+            Some(false) if box_below - (bottom + box_size) >= reversal => {
+                bars.push(column(bottom, top, false, start, end, volume));
+                bottom += box_size;
+                top = box_below;
+                rising = Some(true);
+                start = candle.timestamp;
+                volume = Volume::default();
+            }
+            Some(_) => {}
+        }
+
+        end = candle.timestamp;
+        volume.add(candle);
+    }
+
+    if let Some(up) = rising {
+        bars.push(column(bottom, top, up, start, end, volume));
+    }
+
+    bars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Candles of one volume each that only move their close, an hour apart
+    fn closes(closes: &[f64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| Candle::new(i as i64 * 3600, close, close, close, close, 1.0))
+            .collect()
+    }
+
+    /// Open, close, first and last candle time, and volume of `bar`
+    fn span(bar: &Bar) -> (f64, f64, i64, i64, f64) {
+        (
+            bar.candle.open,
+            bar.candle.close,
+            bar.candle.timestamp / 3600,
+            bar.end / 3600,
+            bar.candle.volume,
+        )
+    }
+
+    #[test]
+    fn renko_adds_a_brick_per_box() {
+        let bars = renko(&closes(&[10.0, 10.4, 12.3, 11.5, 10.9, 9.0]), 1.0, 1);
+        let spans: Vec<_> = bars.iter().map(span).collect();
+
+        assert_eq!(
+            spans,
+            [
+                // Two boxes in one candle, the first brick carries the volume
+                (10.0, 11.0, 0, 2, 3.0),
+                (11.0, 12.0, 0, 2, 0.0),
+                // Turns once the close is a box past the last brick's open
+                (11.0, 10.0, 3, 5, 3.0),
+                (10.0, 9.0, 3, 5, 0.0),
+            ]
+        );
+        assert!(
+            bars.iter()
+                .all(|bar| bar.candle.high == bar.candle.open.max(bar.candle.close))
+        );
+    }
+
+    #[test]
+    fn renko_starts_on_the_box_grid() {
+        let bars = renko(&closes(&[10.4, 11.0, 11.2]), 1.0, 1);
+        let spans: Vec<_> = bars.iter().map(span).collect();
+
+        assert_eq!(spans, [(10.0, 11.0, 0, 1, 2.0)]);
+    }
+
+    #[test]
+    fn renko_turns_on_the_reversal_boxes() {
+        let candles = closes(&[10.0, 11.0, 9.5, 9.0, 8.0]);
+
+        // One box past the open is not enough with a reversal of two
+        let spans: Vec<_> = renko(&candles[..4], 1.0, 2).iter().map(span).collect();
+        assert_eq!(spans, [(10.0, 11.0, 0, 1, 2.0)]);
+
+        let spans: Vec<_> = renko(&candles, 1.0, 2).iter().map(span).collect();
+        assert_eq!(
+            spans,
+            [
+                (10.0, 11.0, 0, 1, 2.0),
+                (10.0, 9.0, 2, 4, 3.0),
+                (9.0, 8.0, 2, 4, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn kagi_turns_on_the_reversal_amount() {
+        let bars = kagi(
+            &closes(&[10.0, 11.0, 13.0, 12.0, 10.5, 11.0, 9.0, 11.0]),
+            2.0,
+        );
+        let spans: Vec<_> = bars.iter().map(span).collect();
+
+        assert_eq!(
+            spans,
+            [
+                // Retracing 1.0 from the extreme of 13 keeps the line going
+                (10.0, 13.0, 0, 3, 4.0),
+                (13.0, 9.0, 4, 6, 3.0),
+                // Exactly the reversal amount turns it, the last line is forming
+                (9.0, 11.0, 7, 7, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn kagi_needs_a_first_move() {
+        assert!(kagi(&closes(&[10.0, 11.0, 9.0]), 2.0).is_empty());
+    }
+
+    #[test]
+    fn point_and_figure_flips_columns() {
+        let candles = closes(&[10.0, 11.5, 13.2, 12.4, 11.0, 10.0, 9.6, 9.0, 13.0]);
+
+        // Two boxes down from the extreme are short of a reversal of three
+        let spans: Vec<_> = point_and_figure(&candles[..7], 1.0, 3)
+            .iter()
+            .map(span)
+            .collect();
+        assert_eq!(spans, [(10.0, 13.0, 0, 6, 7.0)]);
+
+        let spans: Vec<_> = point_and_figure(&candles, 1.0, 3)
+            .iter()
+            .map(span)
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (10.0, 13.0, 0, 6, 7.0),
+                // Each column starts a box away from the last one's extreme
+                (12.0, 9.0, 7, 7, 1.0),
+                (10.0, 13.0, 8, 8, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn point_and_figure_waits_for_a_whole_box() {
+        assert!(point_and_figure(&closes(&[10.0, 10.9, 9.1]), 1.0, 3).is_empty());
+    }
+}
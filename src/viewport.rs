@@ -0,0 +1,203 @@
+use crate::axis;
+use crate::candlestick::Candle;
+use iced::Rectangle;
+use std::fmt;
+use std::ops::Range;
+
+/// How prices are mapped onto the y-axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScaleMode {
+    #[default]
+    Linear,
+    /// Equal distances for equal ratios, for long histories
+    Logarithmic,
+    /// Linear, labelled as the change from the first visible close
+    Percentage,
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 3] = [ScaleMode::Linear, ScaleMode::Logarithmic, ScaleMode::Percentage];
+
+    /// Map a price into the space where the axis is linear
+    pub fn forward(self, price: f64) -> f64 {
+        match self {
+            ScaleMode::Logarithmic => price.max(f64::MIN_POSITIVE).ln(),
+            ScaleMode::Linear | ScaleMode::Percentage => price,
+        }
+    }
+
+    /// Inverse of [`ScaleMode::forward`]
+    pub fn inverse(self, value: f64) -> f64 {
+        match self {
+            ScaleMode::Logarithmic => value.exp(),
+            ScaleMode::Linear | ScaleMode::Percentage => value,
+        }
+    }
+}
+
+impl fmt::Display for ScaleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ScaleMode::Linear => "Linear",
+            ScaleMode::Logarithmic => "Log",
+            ScaleMode::Percentage => "Percent",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Range of prices covered by the y-axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl PriceRange {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Range from the lowest low to the highest high of `candles`
+    pub fn of(candles: &[Candle]) -> Option<Self> {
+        let min = candles.iter().map(|c| c.low).reduce(f64::min)?;
+        let max = candles.iter().map(|c| c.high).reduce(f64::max)?;
+        Some(Self::new(min, max))
+    }
+
+    pub fn span(&self) -> f64 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> f64 {
+        (self.min + self.max) / 2.0
+    }
+}
+
+/// The part of the series shown by the chart: a range of bars on the x-axis
+/// and a range of prices on the y-axis
+///
+/// Bar positions are indices into the series and may be fractional, bar `i`
+/// spans `i..i + 1`. Prices are placed according to `mode`; `base` is the price
+/// shown as 0% in [`ScaleMode::Percentage`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub start: f64,
+    pub end: f64,
+    pub price: PriceRange,
+    pub mode: ScaleMode,
+    pub base: f64,
+}
+
+impl Viewport {
+    /// Viewport over bars `start..end` with the price range auto-fitted to the
+    /// candles in view and `values` drawn along them, such as indicators, then
+    /// scaled by `price_scale` (1.0 for a plain fit)
+    pub fn fit(
+        candles: &[Candle],
+        values: impl IntoIterator<Item = f64>,
+        start: f64,
+        end: f64,
+        price_scale: f64,
+        mode: ScaleMode,
+    ) -> Option<Self> {
+        let mut viewport = Self {
+            start,
+            end,
+            price: PriceRange::new(0.0, 1.0),
+            mode,
+            base: 1.0,
+        };
+
+        let visible = &candles[viewport.visible(candles.len())];
+        let mut range = PriceRange::of(visible)?;
+        for value in values.into_iter().filter(|value| value.is_finite()) {
+            range = PriceRange::new(range.min.min(value), range.max.max(value));
+        }
+        viewport.base = visible[0].close;
+
+        // Pad and scale where the axis is linear, so log charts stay balanced
+        let low = mode.forward(range.min);
+        let high = mode.forward(range.max);
+        let span = match high - low {
+            // Keep a flat series from collapsing into a zero-height range
+            span if span > 0.0 => span,
+            _ => high.abs().max(1.0) * 0.02,
+        };
+        let half = span * 1.2 * price_scale / 2.0;
+        let center = (low + high) / 2.0;
+        viewport.price = PriceRange::new(mode.inverse(center - half), mode.inverse(center + half));
+
+        Some(viewport)
+    }
+
+    /// Number of bars across the chart width
+    pub fn bars(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Indices of the bars at least partly in view
+    pub fn visible(&self, len: usize) -> Range<usize> {
+        let start = self.start.floor().max(0.0) as usize;
+        let end = (self.end.ceil().max(0.0) as usize).min(len);
+        start.min(end)..end
+    }
+
+    /// Width of one bar in pixels
+    pub fn bar_width(&self, area: Rectangle) -> f32 {
+        area.width / self.bars() as f32
+    }
+
+    /// X coordinate of the left edge of bar position `index`
+    pub fn x(&self, index: f64, area: Rectangle) -> f32 {
+        area.x + ((index - self.start) / self.bars()) as f32 * area.width
+    }
+
+    /// Bar position under the x coordinate
+    pub fn index_at(&self, x: f32, area: Rectangle) -> f64 {
+        self.start + ((x - area.x) / area.width) as f64 * self.bars()
+    }
+
+    /// Y coordinate of `price`
+    pub fn y(&self, price: f64, area: Rectangle) -> f32 {
+        let low = self.mode.forward(self.price.min);
+        let high = self.mode.forward(self.price.max);
+        let ratio = (self.mode.forward(price) - low) / (high - low);
+        area.y + area.height - ratio as f32 * area.height
+    }
+
+    /// Price at the y coordinate
+    pub fn price_at(&self, y: f32, area: Rectangle) -> f64 {
+        let low = self.mode.forward(self.price.min);
+        let high = self.mode.forward(self.price.max);
+        let ratio = ((area.y + area.height - y) / area.height) as f64;
+        self.mode.inverse(low + ratio * (high - low))
+    }
+
+    /// Change of `price` from the base, in percent
+    pub fn percent(&self, price: f64) -> f64 {
+        (price / self.base - 1.0) * 100.0
+    }
+
+    /// Prices to put grid lines and labels at, at most about `max_count`
+    pub fn price_ticks(&self, max_count: usize) -> Vec<f64> {
+        match self.mode {
+            ScaleMode::Linear => axis::nice_ticks(self.price.min, self.price.max, max_count),
+            ScaleMode::Logarithmic => axis::log_ticks(self.price.min, self.price.max, max_count),
+            // Round percentages rather than round prices
+            ScaleMode::Percentage => axis::nice_ticks(self.percent(self.price.min), self.percent(self.price.max), max_count)
+                .into_iter()
+                .map(|percent| self.base * (1.0 + percent / 100.0))
+                .collect(),
+        }
+    }
+
+    /// Axis label for `price` with `precision` decimals, as a percentage in
+    /// [`ScaleMode::Percentage`]
+    pub fn label(&self, price: f64, precision: usize) -> String {
+        match self.mode {
+            ScaleMode::Percentage => format!("{:+.2}%", self.percent(price)),
+            ScaleMode::Linear | ScaleMode::Logarithmic => format!("{:.*}", precision, price),
+        }
+    }
+}